@@ -0,0 +1,216 @@
+use ratatui::style::{Color, Modifier, Style};
+use std::ops::Range;
+
+const ESC: char = '\x1b';
+
+/// Styles set by ANSI escape sequences in a line, as byte ranges of the
+/// escape-stripped text.
+pub type AnsiStyles = Vec<(Range<usize>, Style)>;
+
+/// Strips ANSI escape sequences from `line`.
+///
+/// SGR (`ESC [ ... m`) sequences are turned into styles of the stripped text,
+/// like `less -R` does. Other escape sequences are dropped.
+///
+/// # Returns
+/// * `(String, AnsiStyles)` - The text without escape sequences and the styles
+///   of the styled parts of it.
+#[must_use]
+pub fn parse(line: &str) -> (String, AnsiStyles) {
+    if !line.contains(ESC) {
+        return (line.to_string(), AnsiStyles::new());
+    }
+    let mut text = String::with_capacity(line.len());
+    let mut styles = AnsiStyles::new();
+    let mut style = Style::new();
+    let mut style_start = 0;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            text.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&']').is_some() {
+            // Operating system command, terminated by BEL or `ESC \`
+            while let Some(c) = chars.next() {
+                if c == '\x07' || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+            continue;
+        }
+        if chars.next_if_eq(&'[').is_none() {
+            // Other escape sequences, such as `ESC ( B`
+            while chars.next_if(|c| (' '..='/').contains(c)).is_some() {}
+            chars.next();
+            continue;
+        }
+        let mut params = String::new();
+        let mut command = None;
+        for c in chars.by_ref() {
+            if ('\x40'..='\x7e').contains(&c) {
+                command = Some(c);
+                break;
+            }
+            params.push(c);
+        }
+        if command != Some('m') {
+            continue;
+        }
+        let new_style = apply_sgr(style, &params);
+        if new_style != style {
+            push_style(&mut styles, style_start..text.len(), style);
+            style = new_style;
+            style_start = text.len();
+        }
+    }
+    push_style(&mut styles, style_start..text.len(), style);
+    (text, styles)
+}
+
+/// Adds a styled range, merging it with the previous one if they have the same
+/// style and are adjacent.
+fn push_style(styles: &mut AnsiStyles, range: Range<usize>, style: Style) {
+    if range.is_empty() || style == Style::new() {
+        return;
+    }
+    match styles.last_mut() {
+        Some((last_range, last_style)) if last_range.end == range.start && *last_style == style => {
+            last_range.end = range.end;
+        }
+        _ => styles.push((range, style)),
+    }
+}
+
+/// Applies the SGR parameters `params` on top of `style`, skipping parameters
+/// that are not valid.
+fn apply_sgr(style: Style, params: &str) -> Style {
+    // An empty parameter, such as in an empty parameter list, is a reset
+    let mut params = params.split([';', ':']).map(|param| {
+        if param.is_empty() {
+            Some(0)
+        } else {
+            param.parse::<u8>().ok()
+        }
+    });
+    let mut style = style;
+    while let Some(param) = params.next() {
+        let Some(param) = param else {
+            continue;
+        };
+        style = match param {
+            0 => Style::new(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed(param - 30)),
+            38 => extended_color(&mut params).map_or(style, |color| style.fg(color)),
+            39 => style.fg(Color::Reset),
+            40..=47 => style.bg(Color::Indexed(param - 40)),
+            48 => extended_color(&mut params).map_or(style, |color| style.bg(color)),
+            49 => style.bg(Color::Reset),
+            90..=97 => style.fg(Color::Indexed(param - 90 + 8)),
+            100..=107 => style.bg(Color::Indexed(param - 100 + 8)),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// Parses the color of a `38` or `48` SGR parameter, either `5;n` for an
+/// indexed color or `2;r;g;b` for a RGB color.
+fn extended_color(params: &mut impl Iterator<Item = Option<u8>>) -> Option<Color> {
+    match params.next().flatten()? {
+        5 => params.next().flatten().map(Color::Indexed),
+        2 => {
+            // Take all components even if one of them is not valid.
+            let (r, g, b) = (
+                params.next().flatten(),
+                params.next().flatten(),
+                params.next().flatten(),
+            );
+            Some(Color::Rgb(r?, g?, b?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+    use ratatui::style::{Color, Modifier, Style};
+
+    #[test]
+    fn plain_text() {
+        let (text, styles) = parse("commit b8e882d50a8e2f184e8803a18818da18dbbd1469");
+        assert_eq!(text, "commit b8e882d50a8e2f184e8803a18818da18dbbd1469");
+        assert!(styles.is_empty());
+    }
+
+    #[test]
+    fn git_colors() {
+        let (text, styles) = parse(
+            "\x1b[33mcommit b8e882d\x1b[m\x1b[33m (\x1b[m\x1b[1;36mHEAD\x1b[m\x1b[33m)\x1b[m",
+        );
+        assert_eq!(text, "commit b8e882d (HEAD)");
+        assert_eq!(
+            styles,
+            vec![
+                (0..16, Style::new().fg(Color::Indexed(3))),
+                (
+                    16..20,
+                    Style::new()
+                        .fg(Color::Indexed(6))
+                        .add_modifier(Modifier::BOLD)
+                ),
+                (20..21, Style::new().fg(Color::Indexed(3))),
+            ]
+        );
+    }
+
+    #[test]
+    fn extended_colors() {
+        let (text, styles) = parse("\x1b[38;5;196;48;2;1;2;3mred\x1b[0m plain");
+        assert_eq!(text, "red plain");
+        assert_eq!(
+            styles,
+            vec![(
+                0..3,
+                Style::new().fg(Color::Indexed(196)).bg(Color::Rgb(1, 2, 3))
+            )]
+        );
+    }
+
+    #[test]
+    fn skip_invalid_parameters() {
+        let (text, styles) = parse("\x1b[1;38;5;300mbold\x1b[38;2;1;999;3;4mline");
+        assert_eq!(text, "boldline");
+        assert_eq!(
+            styles,
+            vec![
+                (0..4, Style::new().add_modifier(Modifier::BOLD)),
+                (
+                    4..8,
+                    Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_other_escapes() {
+        let (text, styles) = parse("\x1b[2Kline\x1b(B end\x1b]8;;https://example.com\x1b\\");
+        assert_eq!(text, "line end");
+        assert!(styles.is_empty());
+    }
+}
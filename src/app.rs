@@ -1,28 +1,156 @@
-use crate::context_finder::{ContextFinder, InputType};
+use crate::commit::Commit;
+use crate::config::Config;
+use crate::context_finder::{Boundary, ContextFinder, InputType};
 use crate::error::Error;
-use crate::input::stream_input;
-use crate::search::{search, SearchDirection, SearchState};
-use crate::ui::pager;
-use crate::utils::{decrement_scroll_position, get_lines, increment_scroll_position};
-use crossterm::event::{read, Event, KeyCode, KeyEvent};
+use crate::fold::Folds;
+use crate::gutter::{gutters, GutterMode};
+use crate::history::SearchHistory;
+use crate::input::{stream_input, Lines};
+use crate::picker::{Picker, PickerItem};
+use crate::search::{
+    search_wrapping, Matcher, Matches, SearchDirection, SearchOptions, SearchState,
+};
+use crate::side_by_side::{split_rows, unified_rows_shown};
+use crate::syntax::SyntaxHighlighter;
+use crate::ui::{pager, Screen, Status};
+use crate::utils::{
+    decrement_scroll_position, get_lines, increment_scroll_position, percent, rows_fitting,
+    wrapped_height,
+};
+use crate::view::View;
+use crate::word_diff::changed_words;
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{backend::Backend, Terminal};
+use std::io::Read;
 use std::sync::mpsc::{Receiver, TryRecvError};
 use std::time::Duration;
-use tracing::{trace, warn};
+use tracing::trace;
 use tui_input::backend::crossterm::EventHandler as _;
+use tui_input::Input;
+use unicode_width::UnicodeWidthStr;
 
 const INPUT_STREAM_TIMEOUT: u64 = 1000;
+/// How often the screen is redrawn while input is still being read, in
+/// milliseconds.
+const STREAMING_REDRAW_INTERVAL: u64 = 100;
+/// Number of columns scrolled left or right at a time.
+const HORIZONTAL_SCROLL_STEP: usize = 8;
 
 pub enum State {
     Exit,
     Pager,
     Search(SearchState),
+    /// Getting the pattern of the lines to show.
+    Filter {
+        term: Input,
+    },
+    /// Getting a command such as `author alice` filtering the commits shown.
+    Command {
+        term: Input,
+    },
+    /// Choosing a place to jump to from a popup.
+    Picker(Picker),
+}
+
+/// State of the application kept between key presses.
+struct App {
+    state: State,
+    /// Row of the view shown at the top of the screen.
+    position: usize,
+    /// Lines shown in the pager.
+    view: View,
+    search_options: SearchOptions,
+    history: SearchHistory,
+    /// Matches of the current search term.
+    matches: Matches,
+    /// Continue searches from the other end of the input when reaching the end.
+    wrap_search: bool,
+    /// Message shown until the next key press.
+    message: Option<String>,
+    /// Show the old and new versions of hunks in two columns.
+    side_by_side: bool,
+    /// Wrap long lines instead of cutting them at the edge of the screen.
+    wrap: bool,
+    /// Number of columns the lines are scrolled to the left.
+    horizontal_scroll: usize,
+    /// Line numbers shown left of the lines.
+    gutter: GutterMode,
+    vertical_size: u16,
+    horizontal_size: u16,
+}
+
+impl App {
+    /// Creates the application state for the initial position and search
+    /// given in `config`.
+    ///
+    /// # Errors
+    /// This function can return errors in the following cases:
+    /// * If a glob of the files to fold is not valid
+    fn new(
+        config: &Config,
+        vertical_size: u16,
+        horizontal_size: u16,
+        all_lines: &[String],
+        cf: &ContextFinder,
+    ) -> Result<Self, Error> {
+        let mut view = View::default();
+        let mut folds = Folds::default();
+        for glob in &config.folds {
+            folds.add_glob(glob)?;
+        }
+        view.set_folds(folds);
+        view.update(all_lines, cf, false);
+        let mut app = App {
+            state: State::Pager,
+            position: increment_scroll_position(
+                0,
+                view.row_of(config.start_line),
+                view.len(),
+                vertical_size,
+                |_row| 1,
+            ),
+            view,
+            search_options: SearchOptions {
+                case: config.case,
+                ..SearchOptions::default()
+            },
+            history: SearchHistory::load(SearchHistory::default_path()),
+            matches: Matches::default(),
+            wrap_search: config.wrap_search,
+            message: None,
+            side_by_side: false,
+            wrap: false,
+            horizontal_scroll: 0,
+            gutter: config.line_numbers,
+            vertical_size,
+            horizontal_size,
+        };
+        if let Some(pattern) = &config.pattern {
+            let term = Input::from(pattern.as_str());
+            let found = search_term(
+                &term,
+                app.search_options,
+                app.position,
+                all_lines,
+                app.view.rows(),
+                &SearchDirection::Forward,
+                app.wrap_search,
+            );
+            go_to_match(&mut app, found, &SearchDirection::Forward);
+            app.state = State::Search(SearchState::Searching {
+                term,
+                position: app.position,
+            });
+        }
+        Ok(app)
+    }
 }
 
 /// Runs the application.
 ///
-/// This function initializes the terminal, sets up the input stream, and enters a loop to handle
-/// user input and update the terminal display accordingly.
+/// This function initializes the terminal, sets up streaming of `input`, and enters a loop to
+/// handle user input and update the terminal display accordingly. The initial position, search
+/// and input type are taken from `config`.
 ///
 /// # Errors
 /// This function can return errors in the following cases:
@@ -30,140 +158,734 @@ pub enum State {
 /// * If there is an error receiving input from the input stream.
 /// * If there is an error drawing to the terminal.
 /// * If there is an error reading user input.
-pub fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<(), Error> {
-    let mut position: usize = 0;
-    let mut vertical_size = terminal.size()?.height;
-    let (rx, _thread_handle) = stream_input(usize::from(vertical_size) * 4);
+pub fn run_app<B: Backend, R: Read + Send + 'static>(
+    terminal: &mut Terminal<B>,
+    input: R,
+    config: &Config,
+) -> Result<(), Error> {
+    let size = terminal.size()?;
+    let vertical_size = size.height;
+    let (rx, _thread_handle) = stream_input(input, usize::from(vertical_size) * 4);
     let mut all_lines = rx.recv_timeout(Duration::from_millis(INPUT_STREAM_TIMEOUT))??;
-    let cf = ContextFinder::new(&InputType::Git)?;
-    let mut state = State::Pager;
+    while all_lines.len() <= config.start_line {
+        trace!("Waiting for start line {}", config.start_line);
+        let Ok(new_lines) = rx.recv() else {
+            break;
+        };
+        all_lines.extend(new_lines?);
+    }
+    let cf = ContextFinder::new(&config.input_type)?;
+    let mut app = App::new(config, vertical_size, size.width, &all_lines.text, &cf)?;
+    let mut highlighter = SyntaxHighlighter::default();
 
     loop {
-        all_lines = handle_new_lines(&rx, all_lines)?;
-        let context = cf.get_context(&all_lines[..], position);
-        let lines = get_lines(&all_lines[..], position, terminal.size()?.height)?;
-
-        let hilights = get_hilights(&state);
-        terminal
-            .try_draw(|frame| pager(frame, &state, lines, context, &mut vertical_size, hilights))?;
+        let streaming = handle_new_lines(&rx, &mut all_lines)?;
+        app.view.update(&all_lines.text, &cf, !streaming);
+        draw_screen(
+            terminal,
+            &mut app,
+            &all_lines,
+            &cf,
+            &mut highlighter,
+            config.input_type,
+            streaming,
+        )?;
 
+        if streaming && !poll(Duration::from_millis(STREAMING_REDRAW_INTERVAL))? {
+            continue;
+        }
         let event = read()?;
         if let Event::Key(key) = event {
-            handle_key_event(key, &mut state, &mut position, &all_lines, vertical_size)?;
-            if let State::Exit = state {
+            handle_key_event(key, &mut app, &all_lines.text, &cf);
+            if let State::Exit = app.state {
                 return Ok(());
             }
         }
     }
 }
 
+/// Draws the screen of the pager for the current state of `app`.
+///
+/// # Errors
+/// This function can return errors in the following cases:
+/// * If there is an error getting the terminal size.
+/// * If there is an error drawing to the terminal.
+fn draw_screen<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    all_lines: &Lines,
+    cf: &ContextFinder,
+    highlighter: &mut SyntaxHighlighter,
+    input_type: InputType,
+    streaming: bool,
+) -> Result<(), Error> {
+    let height = terminal.size()?.height;
+    let (term, matcher) = get_matcher(&app.state, app.search_options).unzip();
+    match (term, &matcher) {
+        (Some(term), Some(Ok(matcher))) => app.matches.update(
+            term,
+            app.search_options,
+            matcher,
+            &all_lines.text,
+            app.view.rows(),
+        ),
+        (_, Some(Err(err))) => app.message = Some(error_message(err)),
+        _ => (),
+    }
+    let contexts = app
+        .view
+        .line(app.position)
+        .map(|line_num| cf.get_contexts(&all_lines.text, line_num))
+        .unwrap_or_default();
+    let commit = app
+        .view
+        .line(app.position)
+        .and_then(|line_num| cf.previous_start(Boundary::Commit, &all_lines.text, line_num + 1))
+        .and_then(|start| cf.get_commit_header(&all_lines.text, start, true));
+    let rows = if app.side_by_side {
+        // Pairing removed and added lines can halve the rows, so twice as
+        // many rows are needed to fill the screen.
+        get_lines(
+            app.view.rows(),
+            app.position,
+            height.saturating_mul(2),
+            |_row| 1,
+        )?
+    } else {
+        get_lines(app.view.rows(), app.position, height, |row| {
+            row_height(app, &all_lines.text, row)
+        })?
+    };
+    let folds: Vec<_> = rows
+        .iter()
+        .map(|line_num| app.view.fold(*line_num).copied())
+        .collect();
+    let in_hunk = rows
+        .first()
+        .is_some_and(|line_num| cf.section_starts(&all_lines.text, *line_num).1.is_some());
+    let changed = changed_words(
+        &all_lines.text,
+        app.view.rows(),
+        app.position,
+        rows.len(),
+        cf,
+        in_hunk,
+    );
+    let split = app
+        .side_by_side
+        .then(|| split_rows(&all_lines.text, rows, cf, in_hunk));
+    let screen = Screen {
+        lines: &all_lines.text,
+        styles: &all_lines.styles,
+        rows,
+        folds: &folds,
+        changed: &changed,
+        syntax: &highlighter.highlight(&all_lines.text, &all_lines.styles, rows, cf),
+        split: split.as_deref(),
+        contexts: &contexts,
+        hilights: matcher.as_ref().and_then(|matcher| matcher.as_ref().ok()),
+        search_options: app.search_options,
+        search_error: matcher.as_ref().is_some_and(Result::is_err),
+        matches: matcher
+            .as_ref()
+            .is_some_and(Result::is_ok)
+            .then(|| app.matches.count(app.position)),
+        message: app.message.as_deref(),
+        gutters: &gutters(app.gutter, &all_lines.text, rows, cf),
+        horizontal_scroll: app.horizontal_scroll,
+        wrap: app.wrap,
+        status: Status {
+            line: app.view.line(app.position),
+            total_lines: all_lines.len(),
+            percent: percent(
+                app.position,
+                page_rows(app, &all_lines.text, cf),
+                app.view.len(),
+            ),
+            commit: commit.as_ref().map(Commit::short_hash),
+            input_type,
+            streaming,
+        },
+    };
+    terminal.try_draw(|frame| {
+        pager(
+            frame,
+            &app.state,
+            &screen,
+            cf,
+            &mut app.vertical_size,
+            &mut app.horizontal_size,
+        )
+    })?;
+    Ok(())
+}
+
+/// Number of lines of the screen the row `row` takes.
+///
+/// The summary of a folded row is cut at the edge of the screen instead of
+/// wrapped, so a folded row takes a single line.
+fn row_height(app: &App, all_lines: &[String], row: usize) -> usize {
+    if !app.wrap || app.side_by_side {
+        return 1;
+    }
+    app.view
+        .line(row)
+        .filter(|line_num| app.view.fold(*line_num).is_none())
+        .and_then(|line_num| all_lines.get(line_num))
+        .map_or(1, |line| wrapped_height(line, app.horizontal_size))
+}
+
+/// Counts the rows of the view shown on the screen from the top of the
+/// screen down to the first row not fitting completely.
+///
+/// # Returns
+/// * `usize` - The number of rows to move a screen forward without skipping
+///   any row, at least one.
+fn page_rows(app: &App, all_lines: &[String], cf: &ContextFinder) -> usize {
+    if !app.side_by_side {
+        return rows_fitting(app.position..app.view.len(), app.vertical_size, |row| {
+            row_height(app, all_lines, row)
+        });
+    }
+    // A screen of split rows shows more rows of the view than its height.
+    let Ok(rows) = get_lines(
+        app.view.rows(),
+        app.position,
+        app.vertical_size.saturating_mul(2),
+        |_row| 1,
+    ) else {
+        return 1;
+    };
+    let in_hunk = rows
+        .first()
+        .is_some_and(|line_num| cf.section_starts(all_lines, *line_num).1.is_some());
+    let split = split_rows(all_lines, rows, cf, in_hunk);
+    unified_rows_shown(&split, usize::from(app.vertical_size)).max(1)
+}
+
+/// Moves `count` rows down from row `position`, stopping at the row showing
+/// the end of the view at the bottom of the screen.
+fn scroll_down(app: &App, all_lines: &[String], position: usize, count: usize) -> usize {
+    increment_scroll_position(position, count, app.view.len(), app.vertical_size, |row| {
+        row_height(app, all_lines, row)
+    })
+}
+
+/// Number of columns the lines can be scrolled to the left until the end of
+/// the widest line on the screen is at the right edge.
+fn max_horizontal_scroll(app: &App, all_lines: &[String]) -> usize {
+    let width = if app.side_by_side {
+        app.horizontal_size / 2
+    } else {
+        app.horizontal_size
+    };
+    get_lines(app.view.rows(), app.position, app.vertical_size, |_row| 1)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|line_num| all_lines.get(*line_num))
+        .map(|line| line.width())
+        .max()
+        .unwrap_or_default()
+        .saturating_sub(usize::from(width))
+}
+
+/// Appends the lines received so far from the input stream to `all_lines`.
+///
+/// # Returns
+/// * `bool` - Whether the input is still being read.
 fn handle_new_lines(
-    rx: &Receiver<Result<Vec<String>, Error>>,
-    mut all_lines: Vec<String>,
-) -> Result<Vec<String>, Error> {
-    match rx.try_recv() {
-        Ok(maybe_new_lines) => {
-            trace!("Got more lines");
-            all_lines.extend(maybe_new_lines?);
-            Ok(all_lines)
-        }
-        Err(TryRecvError::Disconnected) => Ok(all_lines),
-        Err(e) => {
-            warn!("Got error receiving new lines: {e}");
-            Ok(all_lines)
+    rx: &Receiver<Result<Lines, Error>>,
+    all_lines: &mut Lines,
+) -> Result<bool, Error> {
+    loop {
+        match rx.try_recv() {
+            Ok(maybe_new_lines) => {
+                trace!("Got more lines");
+                all_lines.extend(maybe_new_lines?);
+            }
+            Err(TryRecvError::Empty) => return Ok(true),
+            Err(TryRecvError::Disconnected) => return Ok(false),
         }
     }
 }
 
-fn get_hilights(state: &State) -> Option<String> {
+/// Compiles the search term of the current search or filter being entered, if
+/// there is a non-empty one.
+fn get_matcher(state: &State, options: SearchOptions) -> Option<(&str, Result<Matcher, Error>)> {
     match state {
         State::Search(
             SearchState::GetInput { ref term } | SearchState::Searching { ref term, .. },
-        ) => Some(term.to_string()),
+        )
+        | State::Filter { ref term }
+            if !term.value().is_empty() =>
+        {
+            Some((term.value(), Matcher::new(term.value(), options)))
+        }
         _ => None,
     }
 }
 
-fn handle_key_event(
-    key: KeyEvent,
-    state: &mut State,
-    position: &mut usize,
+/// Text of `err` for the status bar, followed by the last line of its cause,
+/// such as why a regular expression is not valid.
+fn error_message(err: &Error) -> String {
+    let cause = std::error::Error::source(err).map(ToString::to_string);
+    match cause.as_deref().and_then(|cause| {
+        cause
+            .lines()
+            .map(|line| line.trim().trim_start_matches("error: "))
+            .rfind(|line| !line.is_empty())
+    }) {
+        Some(reason) => format!("{err}: {reason}"),
+        None => err.to_string(),
+    }
+}
+
+/// Searches for `term` starting from the row `position`, treating a term that
+/// is not a valid pattern as having no matches.
+///
+/// # Returns
+/// * `Option<(usize, bool)>` - The row of the match and whether the search
+///   wrapped around to find it, or None if there is no match.
+fn search_term(
+    term: &Input,
+    options: SearchOptions,
+    position: usize,
     all_lines: &[String],
-    vertical_size: u16,
-) -> Result<(), Error> {
-    match state {
-        State::Pager => match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => *state = State::Exit,
-            KeyCode::Char('j') | KeyCode::Down => {
-                *position = increment_scroll_position(*position, 1, all_lines.len(), vertical_size);
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                *position = decrement_scroll_position(*position, 1);
-            }
-            KeyCode::PageDown => {
-                *position = increment_scroll_position(
-                    *position,
-                    usize::from(vertical_size),
-                    all_lines.len(),
-                    vertical_size,
-                );
-            }
-            KeyCode::PageUp => {
-                *position = decrement_scroll_position(*position, usize::from(vertical_size));
+    rows: &[usize],
+    direction: &SearchDirection,
+    wrap: bool,
+) -> Option<(usize, bool)> {
+    match Matcher::new(term.value(), options) {
+        Ok(matcher) => search_wrapping(&matcher, position, all_lines, rows, direction, wrap),
+        Err(err) => {
+            trace!("Invalid search term: {err}");
+            None
+        }
+    }
+}
+
+/// Moves to the match `found` by a search in `direction`, telling in the
+/// message if the search wrapped around or stopped at the end of the input.
+fn go_to_match(app: &mut App, found: Option<(usize, bool)>, direction: &SearchDirection) {
+    let (end, start) = match direction {
+        SearchDirection::Backwards => ("TOP", "BOTTOM"),
+        SearchDirection::Forward => ("BOTTOM", "TOP"),
+    };
+    match found {
+        Some((row, wrapped)) => {
+            app.position = row;
+            if wrapped {
+                app.message = Some(format!("search hit {end}, continuing at {start}"));
             }
-            KeyCode::Char('/') => {
-                *state = State::Search(SearchState::GetInput { term: "".into() });
+        }
+        None if !app.wrap_search && !app.matches.is_empty() => {
+            app.message = Some(format!("search hit {end} without match"));
+        }
+        None => (),
+    }
+}
+
+/// Moves to the row `new_position` if there is one, keeping the screen full.
+fn jump_to(app: &App, all_lines: &[String], new_position: Option<usize>) -> usize {
+    new_position.map_or(app.position, |new_position| {
+        scroll_down(app, all_lines, new_position, 0)
+    })
+}
+
+/// Finds the row of the next or previous start of `boundary` from the row at
+/// the top of the screen, skipping the starts whose rows are hidden.
+fn boundary_row(
+    app: &App,
+    cf: &ContextFinder,
+    all_lines: &[String],
+    boundary: Boundary,
+    direction: &SearchDirection,
+) -> Option<usize> {
+    let mut line_num = app.view.line(app.position)?;
+    match direction {
+        SearchDirection::Forward => cf
+            .next_start(boundary, all_lines, line_num)
+            .map(|start| app.view.row_of(start)),
+        SearchDirection::Backwards => loop {
+            line_num = cf.previous_start(boundary, all_lines, line_num)?;
+            let row = app.view.row_of(line_num);
+            if row < app.position {
+                return Some(row);
             }
-            _ => (),
         },
-        State::Search(SearchState::GetInput { ref mut term }) => match key.code {
-            KeyCode::Esc => *state = State::Pager,
-            KeyCode::Enter => {
-                *state = State::Search(SearchState::Searching {
-                    term: term.clone(),
-                    position: *position,
-                });
-            }
-            _ => {
-                *position = if let Some(new_position) =
-                    search(term, *position, all_lines, &SearchDirection::Forward)?
-                {
-                    new_position
-                } else {
-                    *position
-                };
-                term.handle_event(&Event::Key(key));
+    }
+}
+
+/// Changes the filters of the view with `change`, keeping the line at the top
+/// of the screen in view if it is still shown.
+fn change_filters(
+    app: &mut App,
+    all_lines: &[String],
+    cf: &ContextFinder,
+    change: impl FnOnce(&mut View),
+) {
+    let line_num = app.view.line(app.position).unwrap_or_default();
+    change(&mut app.view);
+    app.view.update(all_lines, cf, false);
+    app.matches = Matches::default();
+    app.position = scroll_down(app, all_lines, app.view.row_of(line_num), 0);
+}
+
+fn handle_key_event(key: KeyEvent, app: &mut App, all_lines: &[String], cf: &ContextFinder) {
+    app.message = None;
+    match app.state {
+        State::Pager => handle_pager_key(key, app, all_lines, cf),
+        State::Filter { .. } => handle_filter_input_key(key, app, all_lines, cf),
+        State::Command { .. } => handle_command_input_key(key, app, all_lines, cf),
+        State::Picker(_) => handle_picker_key(key, app, all_lines),
+        State::Search(SearchState::GetInput { .. }) => {
+            handle_search_input_key(key, app, all_lines);
+        }
+        State::Search(SearchState::Searching { .. }) => {
+            handle_searching_key(key, app, all_lines);
+        }
+        State::Exit => unreachable!(),
+    }
+}
+
+fn handle_pager_key(key: KeyEvent, app: &mut App, all_lines: &[String], cf: &ContextFinder) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.state = State::Exit,
+        KeyCode::Char('j') | KeyCode::Down => {
+            app.position = scroll_down(app, all_lines, app.position, 1);
+        }
+        KeyCode::Char('k') | KeyCode::Up => {
+            app.position = decrement_scroll_position(app.position, 1);
+        }
+        KeyCode::PageDown => {
+            let rows = page_rows(app, all_lines, cf);
+            app.position = scroll_down(app, all_lines, app.position, rows);
+        }
+        KeyCode::PageUp => {
+            let rows = rows_fitting((0..app.position).rev(), app.vertical_size, |row| {
+                row_height(app, all_lines, row)
+            });
+            app.position = decrement_scroll_position(app.position, rows);
+        }
+        KeyCode::Char('h') | KeyCode::Left => {
+            app.horizontal_scroll = app.horizontal_scroll.saturating_sub(HORIZONTAL_SCROLL_STEP);
+        }
+        KeyCode::Char('l') | KeyCode::Right => {
+            let max_scroll = max_horizontal_scroll(app, all_lines).max(app.horizontal_scroll);
+            app.horizontal_scroll =
+                (app.horizontal_scroll + HORIZONTAL_SCROLL_STEP).min(max_scroll);
+        }
+        KeyCode::Char('w') => app.wrap = !app.wrap,
+        KeyCode::Char('#') => app.gutter = app.gutter.next(),
+        KeyCode::Char(key @ ('}' | '{' | ']' | '[' | ')' | '(')) => {
+            let boundary = match key {
+                '}' | '{' => Boundary::Commit,
+                ']' | '[' => Boundary::File,
+                _ => Boundary::Hunk,
+            };
+            let direction = if matches!(key, '}' | ']' | ')') {
+                SearchDirection::Forward
+            } else {
+                SearchDirection::Backwards
+            };
+            let new_position = boundary_row(app, cf, all_lines, boundary, &direction);
+            app.position = jump_to(app, all_lines, new_position);
+        }
+        KeyCode::Char('/') => start_search(app),
+        KeyCode::Char('&') => app.state = State::Filter { term: "".into() },
+        KeyCode::Char(':') => app.state = State::Command { term: "".into() },
+        KeyCode::Char('z') => toggle_fold(app, all_lines, cf, Boundary::Hunk),
+        KeyCode::Char('Z') => toggle_fold(app, all_lines, cf, Boundary::File),
+        KeyCode::Char('s') => app.side_by_side = !app.side_by_side,
+        KeyCode::Char('o') => open_outline(app, all_lines, cf),
+        KeyCode::Char('f') => open_file_list(app, all_lines, cf),
+        _ => (),
+    }
+}
+
+fn start_search(app: &mut App) {
+    app.history.reset();
+    app.state = State::Search(SearchState::GetInput { term: "".into() });
+}
+
+fn handle_search_input_key(key: KeyEvent, app: &mut App, all_lines: &[String]) {
+    let State::Search(SearchState::GetInput { ref mut term }) = app.state else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => {
+            app.history.reset();
+            app.state = State::Pager;
+            return;
+        }
+        KeyCode::Enter => {
+            app.history.add(term.value());
+            app.state = State::Search(SearchState::Searching {
+                term: term.clone(),
+                position: app.position,
+            });
+            return;
+        }
+        KeyCode::Up => {
+            if let Some(entry) = app.history.older(term.value()) {
+                *term = Input::from(entry);
             }
-        },
-        State::Search(SearchState::Searching {
-            ref mut term,
-            position: _,
-        }) => match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => *state = State::Pager,
-            KeyCode::Char('n') => {
-                *position = if let Some(new_position) =
-                    search(term, *position + 1, all_lines, &SearchDirection::Forward)?
-                {
-                    new_position
-                } else {
-                    *position
-                };
+        }
+        KeyCode::Down => {
+            if let Some(entry) = app.history.newer() {
+                *term = Input::from(entry);
             }
-            KeyCode::Char('N') => {
-                *position = if let Some(new_position) =
-                    search(term, *position, all_lines, &SearchDirection::Backwards)?
-                {
-                    new_position
-                } else {
-                    *position
-                };
+        }
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.search_options.regex = !app.search_options.regex;
+        }
+        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.search_options.case = app.search_options.case.next();
+        }
+        _ => {
+            term.handle_event(&Event::Key(key));
+        }
+    }
+    let found = search_term(
+        term,
+        app.search_options,
+        app.position,
+        all_lines,
+        app.view.rows(),
+        &SearchDirection::Forward,
+        app.wrap_search,
+    );
+    go_to_match(app, found, &SearchDirection::Forward);
+}
+
+fn handle_filter_input_key(key: KeyEvent, app: &mut App, all_lines: &[String], cf: &ContextFinder) {
+    let State::Filter { ref mut term } = app.state else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.state = State::Pager,
+        KeyCode::Enter if term.value().is_empty() => {
+            change_filters(app, all_lines, cf, |view| view.set_filter(None));
+            app.state = State::Pager;
+        }
+        KeyCode::Enter => {
+            if let Ok(filter) = Matcher::new(term.value(), app.search_options) {
+                change_filters(app, all_lines, cf, |view| view.set_filter(Some(filter)));
+                app.state = State::Pager;
             }
-            KeyCode::Char('/') => {
-                *state = State::Search(SearchState::GetInput { term: "".into() });
+        }
+        KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.search_options.regex = !app.search_options.regex;
+        }
+        KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            app.search_options.case = app.search_options.case.next();
+        }
+        _ => {
+            term.handle_event(&Event::Key(key));
+        }
+    }
+}
+
+/// Runs a command entered after `:`, either `fold GLOB`, `unfold [GLOB]` or a
+/// command of `CommitFilter::apply`.
+fn run_command(
+    app: &mut App,
+    command: &str,
+    all_lines: &[String],
+    cf: &ContextFinder,
+) -> Result<(), Error> {
+    let (name, arg) = command
+        .trim()
+        .split_once(char::is_whitespace)
+        .map_or((command.trim(), ""), |(name, arg)| (name, arg.trim()));
+    match name {
+        "fold" | "unfold" => {
+            let mut folds = app.view.folds().clone();
+            match (name, arg) {
+                ("fold", "") => return Err(Error::Command(command.trim().to_string())),
+                ("fold", glob) => folds.add_glob(glob)?,
+                (_, "") => folds.clear(),
+                (_, glob) => folds.remove_glob(glob)?,
             }
-            _ => (),
-        },
-        State::Exit => unreachable!(),
+            change_filters(app, all_lines, cf, |view| view.set_folds(folds));
+        }
+        _ => {
+            let mut commit_filter = app.view.commit_filter().clone();
+            commit_filter.apply(command)?;
+            change_filters(app, all_lines, cf, |view| {
+                view.set_commit_filter(commit_filter);
+            });
+        }
     }
     Ok(())
 }
+
+/// Folds or unfolds the hunk of the top line, or its file diff if `boundary`
+/// is `Boundary::File` or the line is not inside a hunk.
+fn toggle_fold(app: &mut App, all_lines: &[String], cf: &ContextFinder, boundary: Boundary) {
+    let Some(line_num) = app.view.line(app.position) else {
+        return;
+    };
+    let (file_start, hunk_start) = cf.section_starts(all_lines, line_num);
+    let start = match boundary {
+        Boundary::Hunk => hunk_start.or(file_start),
+        Boundary::Commit | Boundary::File => file_start,
+    };
+    let Some(start) = start else {
+        return;
+    };
+    let mut folds = app.view.folds().clone();
+    folds.toggle(start);
+    change_filters(app, all_lines, cf, |view| view.set_folds(folds));
+    app.position = jump_to(app, all_lines, Some(app.view.row_of(start)));
+}
+
+fn handle_command_input_key(
+    key: KeyEvent,
+    app: &mut App,
+    all_lines: &[String],
+    cf: &ContextFinder,
+) {
+    let State::Command { ref mut term } = app.state else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.state = State::Pager,
+        KeyCode::Enter => {
+            let command = term.value().to_string();
+            match run_command(app, &command, all_lines, cf) {
+                Ok(()) => app.state = State::Pager,
+                Err(err) => app.message = Some(err.to_string()),
+            }
+        }
+        _ => {
+            term.handle_event(&Event::Key(key));
+        }
+    }
+}
+
+/// Width of the author column of the commit outline.
+const OUTLINE_AUTHOR_WIDTH: usize = 16;
+
+/// Opens a popup listing the commits read so far that are shown, with the
+/// commit of the top line selected.
+fn open_outline(app: &mut App, all_lines: &[String], cf: &ContextFinder) {
+    let commits = cf.get_commits(all_lines);
+    let ends = commits
+        .iter()
+        .skip(1)
+        .map(|commit| commit.line)
+        .chain([all_lines.len()]);
+    let items = commits
+        .iter()
+        .zip(ends)
+        .filter(|(commit, end)| app.view.shows_any(commit.line..*end))
+        .map(|(commit, _end)| PickerItem {
+            text: outline_text(commit),
+            line: commit.line,
+        })
+        .collect();
+    let mut picker = Picker::new("Commits", items);
+    if let Some(line_num) = app.view.line(app.position) {
+        picker.select_line(line_num);
+    }
+    app.state = State::Picker(picker);
+}
+
+/// Opens a popup listing the files changed in the commit of the top line with
+/// their diffstat, with the file of the top line selected.
+fn open_file_list(app: &mut App, all_lines: &[String], cf: &ContextFinder) {
+    let Some(line_num) = app.view.line(app.position) else {
+        return;
+    };
+    let Some(commit) = cf.get_commit(all_lines, line_num) else {
+        return;
+    };
+    let items = commit
+        .files
+        .iter()
+        .zip(commit.diffstat())
+        .map(|(file, text)| PickerItem {
+            text,
+            line: file.line,
+        })
+        .collect();
+    let mut picker = Picker::new(&format!("Files of {}", commit.short_hash()), items);
+    picker.select_line(line_num);
+    app.state = State::Picker(picker);
+}
+
+/// Line of the commit outline, such as `abc1234  Author  subject`.
+fn outline_text(commit: &Commit) -> String {
+    let author: String = commit
+        .author_name()
+        .chars()
+        .take(OUTLINE_AUTHOR_WIDTH)
+        .collect();
+    format!(
+        "{}  {author:<OUTLINE_AUTHOR_WIDTH$}  {}",
+        commit.short_hash(),
+        commit.subject()
+    )
+}
+
+fn handle_picker_key(key: KeyEvent, app: &mut App, all_lines: &[String]) {
+    let State::Picker(ref mut picker) = app.state else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc => app.state = State::Pager,
+        KeyCode::Enter => {
+            let row = picker
+                .selected_item()
+                .map(|item| app.view.row_of(item.line));
+            app.state = State::Pager;
+            app.position = jump_to(app, all_lines, row);
+        }
+        KeyCode::Up => picker.select_previous(),
+        KeyCode::Down => picker.select_next(),
+        KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            picker.select_previous();
+        }
+        KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            picker.select_next();
+        }
+        _ => {
+            if picker
+                .query
+                .handle_event(&Event::Key(key))
+                .is_some_and(|changed| changed.value)
+            {
+                picker.update_matches();
+            }
+        }
+    }
+}
+
+fn handle_searching_key(key: KeyEvent, app: &mut App, all_lines: &[String]) {
+    let State::Search(SearchState::Searching { ref term, .. }) = app.state else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => app.state = State::Pager,
+        KeyCode::Char(key @ ('n' | 'N')) => {
+            let (start, direction) = if key == 'n' {
+                (app.position + 1, SearchDirection::Forward)
+            } else {
+                (app.position, SearchDirection::Backwards)
+            };
+            let found = search_term(
+                term,
+                app.search_options,
+                start,
+                all_lines,
+                app.view.rows(),
+                &direction,
+                app.wrap_search,
+            );
+            go_to_match(app, found, &direction);
+        }
+        KeyCode::Char('/') => start_search(app),
+        _ => (),
+    }
+}
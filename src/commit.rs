@@ -0,0 +1,230 @@
+use chrono::{DateTime, FixedOffset, Utc};
+
+/// Date format of the `Date:` line in the default `git log` output.
+const GIT_DATE_FORMAT: &str = "%a %b %e %H:%M:%S %Y %z";
+
+/// Length of an abbreviated commit hash.
+const SHORT_HASH_LEN: usize = 7;
+
+/// Width of the longest bar of `+` and `-` signs of a diffstat.
+const DIFFSTAT_BAR_WIDTH: usize = 30;
+
+/// A commit parsed from the input.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Commit {
+    /// Line number of the commit header in the input.
+    pub line: usize,
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub message: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// The diff of a single file in a commit.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FileDiff {
+    /// Line number of the `diff --git` header in the input.
+    pub line: usize,
+    pub old_path: String,
+    pub new_path: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl Commit {
+    /// The abbreviated commit hash.
+    #[must_use]
+    pub fn short_hash(&self) -> &str {
+        self.hash.get(..SHORT_HASH_LEN).unwrap_or(&self.hash)
+    }
+
+    /// The author name without the email address.
+    #[must_use]
+    pub fn author_name(&self) -> &str {
+        self.author
+            .split_once(" <")
+            .map_or(self.author.as_str(), |(name, _email)| name)
+            .trim()
+    }
+
+    /// The first line of the commit message.
+    #[must_use]
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+
+    /// The commit date, if it is in the default git date format.
+    #[must_use]
+    pub fn timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_str(&self.date, GIT_DATE_FORMAT).ok()
+    }
+
+    /// One line summary of the commit, such as
+    /// `abc1234 · Author · 3 days ago · subject`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        self.summary_at(Utc::now())
+    }
+
+    /// Lines of a `git diff --stat` style summary of the changed files, such
+    /// as `src/main.rs | 12 ++++++------`, in the order of `files`.
+    #[must_use]
+    pub fn diffstat(&self) -> Vec<String> {
+        let path_width = self
+            .files
+            .iter()
+            .map(|file| file.path().chars().count())
+            .max()
+            .unwrap_or_default();
+        let max_changes = self
+            .files
+            .iter()
+            .map(|file| file.added + file.removed)
+            .max()
+            .unwrap_or_default();
+        let count_width = max_changes.to_string().len();
+        let scale = |count: usize| {
+            if max_changes <= DIFFSTAT_BAR_WIDTH || count == 0 {
+                count
+            } else {
+                (count * DIFFSTAT_BAR_WIDTH / max_changes).max(1)
+            }
+        };
+        self.files
+            .iter()
+            .map(|file| {
+                format!(
+                    "{:<path_width$} | {:>count_width$} {}{}",
+                    file.path(),
+                    file.added + file.removed,
+                    "+".repeat(scale(file.added)),
+                    "-".repeat(scale(file.removed)),
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+
+    fn summary_at(&self, now: DateTime<Utc>) -> String {
+        let date = self
+            .timestamp()
+            .map_or_else(|| self.date.clone(), |date| relative_time(date, now));
+        [self.short_hash(), self.author_name(), &date, self.subject()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+impl FileDiff {
+    /// The path of the file after the change.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.new_path
+    }
+}
+
+/// Formats the time from `then` to `now` in a human readable form, such as
+/// `3 days ago`.
+fn relative_time(then: DateTime<FixedOffset>, now: DateTime<Utc>) -> String {
+    let seconds = (now - then.with_timezone(&Utc)).num_seconds();
+    if seconds < 0 {
+        return "in the future".to_string();
+    }
+    let (count, unit) = [
+        (60 * 60 * 24 * 365, "year"),
+        (60 * 60 * 24 * 30, "month"),
+        (60 * 60 * 24 * 7, "week"),
+        (60 * 60 * 24, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ]
+    .into_iter()
+    .find(|(unit_seconds, _unit)| seconds >= *unit_seconds)
+    .map_or((seconds, "second"), |(unit_seconds, unit)| {
+        (seconds / unit_seconds, unit)
+    });
+    if count == 1 {
+        format!("1 {unit} ago")
+    } else {
+        format!("{count} {unit}s ago")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Commit, FileDiff};
+    use chrono::{DateTime, Utc};
+
+    fn commit() -> Commit {
+        Commit {
+            line: 0,
+            hash: "b8e882d50a8e2f184e8803a18818da18dbbd1469".to_string(),
+            author: "Mr. Example <mr@example.com.com>".to_string(),
+            date: "Wed Apr 12 17:49:27 2023 +0300".to_string(),
+            message: "Read stdin\n\nLonger description".to_string(),
+            files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let commit = commit();
+        assert_eq!(commit.short_hash(), "b8e882d");
+        assert_eq!(commit.author_name(), "Mr. Example");
+        assert_eq!(commit.subject(), "Read stdin");
+        assert!(commit.timestamp().is_some());
+    }
+
+    #[test]
+    fn summary() {
+        let now: DateTime<Utc> = "2023-04-15T18:00:00Z".parse().unwrap();
+        assert_eq!(
+            commit().summary_at(now),
+            "b8e882d · Mr. Example · 3 days ago · Read stdin"
+        );
+    }
+
+    #[test]
+    fn summary_with_unknown_date_format() {
+        let commit = Commit {
+            date: "2023-04-12".to_string(),
+            ..commit()
+        };
+        let now: DateTime<Utc> = "2023-04-15T18:00:00Z".parse().unwrap();
+        assert_eq!(
+            commit.summary_at(now),
+            "b8e882d · Mr. Example · 2023-04-12 · Read stdin"
+        );
+    }
+
+    #[test]
+    fn diffstat() {
+        let file = |path: &str, added, removed| FileDiff {
+            old_path: path.to_string(),
+            new_path: path.to_string(),
+            added,
+            removed,
+            ..FileDiff::default()
+        };
+        let commit = Commit {
+            files: vec![
+                file("Cargo.toml", 2, 1),
+                file("src/main.rs", 63, 57),
+                file("README", 0, 0),
+            ],
+            ..commit()
+        };
+        assert_eq!(
+            commit.diffstat(),
+            vec![
+                "Cargo.toml  |   3 +-".to_string(),
+                format!("src/main.rs | 120 {}{}", "+".repeat(15), "-".repeat(14)),
+                "README      |   0".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::commit::Commit;
+use crate::error::Error;
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+
+/// Date format of the `since` and `until` commands.
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Criteria of the commits to show, set with commands such as `author alice`
+/// or `since 2024-01-01`.
+#[derive(Clone, Debug, Default)]
+pub struct CommitFilter {
+    author: Option<Regex>,
+    message: Option<Regex>,
+    /// First day of the commits to show, inclusive.
+    since: Option<NaiveDate>,
+    /// Last day of the commits to show, inclusive.
+    until: Option<NaiveDate>,
+}
+
+impl CommitFilter {
+    /// Applies `command` on top of the current criteria.
+    ///
+    /// The commands are `author PATTERN`, `message PATTERN`, `since DATE` and
+    /// `until DATE`, where the patterns are case insensitive regular
+    /// expressions and the dates are given as `YYYY-MM-DD`. A command without
+    /// an argument clears its criterion, and `all` clears all of them.
+    ///
+    /// # Errors
+    /// This function can return errors in the following cases:
+    /// * If the command is not known
+    /// * If the pattern is not a valid regular expression
+    /// * If the date is not valid
+    pub fn apply(&mut self, command: &str) -> Result<(), Error> {
+        let (name, arg) = command
+            .trim()
+            .split_once(char::is_whitespace)
+            .map_or((command.trim(), ""), |(name, arg)| (name, arg.trim()));
+        match name {
+            "all" if arg.is_empty() => *self = CommitFilter::default(),
+            "author" => self.author = parse_pattern(arg)?,
+            "message" => self.message = parse_pattern(arg)?,
+            "since" => self.since = parse_date(arg)?,
+            "until" => self.until = parse_date(arg)?,
+            _ => return Err(Error::Command(command.trim().to_string())),
+        }
+        Ok(())
+    }
+
+    /// Whether all commits are shown.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.author.is_none()
+            && self.message.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+    }
+
+    /// Checks whether `commit` meets all of the criteria. Commits without a
+    /// date in the git date format are not shown when filtering by date.
+    #[must_use]
+    pub fn matches(&self, commit: &Commit) -> bool {
+        let date = commit.timestamp().map(|timestamp| timestamp.date_naive());
+        self.author
+            .as_ref()
+            .is_none_or(|author| author.is_match(&commit.author))
+            && self
+                .message
+                .as_ref()
+                .is_none_or(|message| message.is_match(&commit.message))
+            && self
+                .since
+                .is_none_or(|since| date.is_some_and(|date| date >= since))
+            && self
+                .until
+                .is_none_or(|until| date.is_some_and(|date| date <= until))
+    }
+}
+
+fn parse_pattern(arg: &str) -> Result<Option<Regex>, Error> {
+    if arg.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(RegexBuilder::new(arg).case_insensitive(true).build()?))
+}
+
+fn parse_date(arg: &str) -> Result<Option<NaiveDate>, Error> {
+    if arg.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(NaiveDate::parse_from_str(arg, DATE_FORMAT)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::CommitFilter;
+    use crate::commit::Commit;
+
+    fn commit(author: &str, date: &str, message: &str) -> Commit {
+        Commit {
+            author: author.to_string(),
+            date: date.to_string(),
+            message: message.to_string(),
+            ..Commit::default()
+        }
+    }
+
+    #[test]
+    fn filter_commits() {
+        let alice = commit(
+            "Alice <alice@example.com>",
+            "Wed Jan 10 10:00:00 2024 +0200",
+            "Fix the parser",
+        );
+        let bob = commit(
+            "Bob <bob@example.com>",
+            "Fri Dec 29 23:30:00 2023 -0500",
+            "Add tests",
+        );
+        let mut filter = CommitFilter::default();
+        assert!(filter.is_empty());
+        filter.apply("author alice").unwrap();
+        assert!(filter.matches(&alice));
+        assert!(!filter.matches(&bob));
+        filter.apply("author").unwrap();
+        filter.apply(" since 2024-01-01 ").unwrap();
+        assert!(filter.matches(&alice));
+        assert!(!filter.matches(&bob));
+        filter.apply("until 2024-01-09").unwrap();
+        assert!(!filter.matches(&alice));
+        filter.apply("all").unwrap();
+        filter.apply("message ^add").unwrap();
+        assert!(!filter.matches(&alice));
+        assert!(filter.matches(&bob));
+    }
+
+    #[test]
+    fn invalid_commands() {
+        let mut filter = CommitFilter::default();
+        assert!(filter.apply("committer alice").is_err());
+        assert!(filter.apply("since yesterday").is_err());
+        assert!(filter.apply("author (").is_err());
+        assert!(filter.is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+use crate::context_finder::InputType;
+use crate::gutter::GutterMode;
+use crate::search::CaseMode;
+use clap::Parser;
+use globset::Glob;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Command line arguments as parsed by `clap`.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Type of the input, used to find the context of the viewed line
+    #[arg(short = 't', long = "type", value_enum, default_value_t = InputType::Git)]
+    input_type: InputType,
+
+    /// Start with a search for PATTERN active
+    #[arg(short, long)]
+    pattern: Option<String>,
+
+    /// How letter case is taken into account in searches
+    #[arg(short, long, value_enum, default_value_t = CaseMode::Insensitive)]
+    case: CaseMode,
+
+    /// Stop searches at the end of the input instead of wrapping around
+    #[arg(long)]
+    no_wrap_search: bool,
+
+    /// Line numbers shown left of the lines, of the input or of the old and
+    /// new versions of the changed files
+    #[arg(short = 'n', long, value_enum, default_value_t = GutterMode::None)]
+    line_numbers: GutterMode,
+
+    /// Start with the diffs of the files matching GLOB folded, such as
+    /// `*.lock` or `vendor/**`. Can be given multiple times.
+    #[arg(long = "fold", value_name = "GLOB", value_parser = parse_glob)]
+    folds: Vec<String>,
+
+    /// Write trace logs to FILE
+    #[arg(long, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Files to show, `-` for standard input. `+N` starts at line N.
+    #[arg(value_name = "FILE|+N")]
+    inputs: Vec<OsString>,
+}
+
+/// Runtime configuration of the pager.
+#[derive(Debug)]
+pub struct Config {
+    pub input_type: InputType,
+    pub pattern: Option<String>,
+    pub case: CaseMode,
+    /// Continue searches from the other end of the input when reaching the
+    /// end.
+    pub wrap_search: bool,
+    /// Globs of the paths of the files whose diffs start folded.
+    pub folds: Vec<String>,
+    pub line_numbers: GutterMode,
+    /// Zero based line number to start the pager at.
+    pub start_line: usize,
+    pub files: Vec<PathBuf>,
+    pub log_file: Option<PathBuf>,
+}
+
+impl Config {
+    /// Parses the configuration from the command line arguments of the process.
+    ///
+    /// Exits the process with a usage message if the arguments are invalid or
+    /// if `--help` or `--version` was requested.
+    #[must_use]
+    pub fn from_args() -> Self {
+        Cli::parse().into()
+    }
+}
+
+impl From<Cli> for Config {
+    fn from(cli: Cli) -> Self {
+        let mut start_line = 0;
+        let mut files = Vec::new();
+        for input in cli.inputs {
+            match input.to_str().and_then(parse_start_line) {
+                Some(line) => start_line = line,
+                None => files.push(PathBuf::from(input)),
+            }
+        }
+        Config {
+            input_type: cli.input_type,
+            pattern: cli.pattern,
+            case: cli.case,
+            wrap_search: !cli.no_wrap_search,
+            folds: cli.folds,
+            line_numbers: cli.line_numbers,
+            start_line,
+            files,
+            log_file: cli.log_file,
+        }
+    }
+}
+
+/// Checks that `arg` is a valid glob for `--fold`.
+fn parse_glob(arg: &str) -> Result<String, globset::Error> {
+    Glob::new(arg)?;
+    Ok(arg.to_string())
+}
+
+/// Parses a one based `+N` start line argument into a zero based line number.
+fn parse_start_line(arg: &str) -> Option<usize> {
+    let line = arg.strip_prefix('+')?;
+    if line.is_empty() || !line.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    line.parse::<usize>()
+        .ok()
+        .map(|line| line.saturating_sub(1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cli, Config};
+    use crate::gutter::GutterMode;
+    use crate::search::CaseMode;
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> Config {
+        Cli::try_parse_from(std::iter::once("cag").chain(args.iter().copied()))
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(&[]);
+        assert_eq!(config.start_line, 0);
+        assert!(config.files.is_empty());
+        assert!(config.pattern.is_none());
+        assert!(config.log_file.is_none());
+        assert_eq!(config.case, CaseMode::Insensitive);
+        assert!(config.wrap_search);
+        assert!(config.folds.is_empty());
+        assert_eq!(config.line_numbers, GutterMode::None);
+    }
+
+    #[test]
+    fn start_line_and_files() {
+        let config = parse(&["a.patch", "+12", "-", "+x"]);
+        assert_eq!(config.start_line, 11);
+        assert_eq!(
+            config.files,
+            vec![
+                PathBuf::from("a.patch"),
+                PathBuf::from("-"),
+                PathBuf::from("+x")
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_file() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = OsStr::from_bytes(b"caf\xe9.patch");
+        let config: Config = Cli::try_parse_from([OsStr::new("cag"), name])
+            .unwrap()
+            .into();
+        assert_eq!(config.files, vec![PathBuf::from(name)]);
+    }
+
+    #[test]
+    fn options() {
+        let config = parse(&[
+            "--type",
+            "git",
+            "-p",
+            "fn main",
+            "--case",
+            "smart",
+            "--log-file",
+            "log",
+            "--no-wrap-search",
+            "--fold",
+            "*.lock",
+            "--fold",
+            "vendor/**",
+            "--line-numbers",
+            "diff",
+        ]);
+        assert_eq!(config.pattern.as_deref(), Some("fn main"));
+        assert_eq!(config.case, CaseMode::Smart);
+        assert_eq!(config.log_file, Some(PathBuf::from("log")));
+        assert!(!config.wrap_search);
+        assert_eq!(config.folds, ["*.lock", "vendor/**"]);
+        assert_eq!(config.line_numbers, GutterMode::Diff);
+    }
+
+    #[test]
+    fn unknown_type() {
+        assert!(Cli::try_parse_from(["cag", "--type", "svn"]).is_err());
+    }
+
+    #[test]
+    fn invalid_fold_glob() {
+        assert!(Cli::try_parse_from(["cag", "--fold", "a["]).is_err());
+    }
+}
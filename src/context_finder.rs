@@ -3,15 +3,70 @@ use std::ops::Range;
 use regex::Regex;
 use tracing::trace;
 
+use crate::commit::{Commit, FileDiff};
 use crate::error::Error;
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, clap::ValueEnum)]
 pub enum InputType {
+    /// Output of `git log --patch`, `git show` and `git format-patch`
     Git,
 }
 
+impl InputType {
+    /// Name of the input type as given to `--type`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            InputType::Git => "git",
+        }
+    }
+}
+
+/// Kind of a boundary between sections of the input.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Boundary {
+    /// Start of a commit
+    Commit,
+    /// Start of the diff of a single file
+    File,
+    /// Start of a hunk inside a file diff
+    Hunk,
+}
+
+/// Kind of a single line of the input, used for coloring it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineKind {
+    /// First line of a commit header
+    Commit,
+    /// Line of a file diff header, such as `diff --git` or `+++ b/file`
+    FileHeader,
+    /// Hunk header line
+    Hunk,
+    /// Line added in a hunk
+    Added,
+    /// Line removed in a hunk
+    Removed,
+    /// Any other line
+    Other,
+}
+
+/// One level of the context of a position, such as the commit or the file
+/// diff it belongs to.
+#[derive(Debug)]
+pub struct Context<'a> {
+    pub boundary: Boundary,
+    pub lines: &'a [String],
+    /// One line summary of the context, shown when there is no room for all
+    /// of the lines.
+    pub summary: String,
+}
+
 pub struct ContextFinder {
     start: Regex,
     end: Regex,
+    file: Regex,
+    file_header: Regex,
+    hunk: Regex,
 }
 
 impl ContextFinder {
@@ -26,19 +81,31 @@ impl ContextFinder {
         match input_type {
             InputType::Git => {
                 trace!("Creating GIT context finder");
-                let start = Regex::new(r"^commit [0-9a-fA-F]{40}")?;
+                let start = Regex::new(r"^commit ([0-9a-fA-F]{40})")?;
                 let end = Regex::new(r"^(commit [0-9a-fA-F]{40}|diff --git)")?;
-                Ok(ContextFinder { start, end })
+                let file = Regex::new(r"^diff --git ")?;
+                let file_header = Regex::new(
+                    r"^(diff --git |index |--- (a/|/dev/null)|\+\+\+ (b/|/dev/null)|(new|deleted) file mode |(old|new) mode |similarity index |rename (from|to) )",
+                )?;
+                let hunk = Regex::new(r"^@@ -(\d+)(,\d+)? \+(\d+)(,\d+)? @@")?;
+                Ok(ContextFinder {
+                    start,
+                    end,
+                    file,
+                    file_header,
+                    hunk,
+                })
             }
         }
     }
 
-    /// Finds the context around a given position in the provided lines.
+    /// Finds the nested contexts around a given position in the provided lines.
     ///
     /// This function searches through the provided lines of text to find the
-    /// context around a specified position. The context is determined by the
-    /// start and end regular expressions defined in the `ContextFinder`. It
-    /// returns a slice of strings representing the context if found.
+    /// commit, the file diff and the hunk the specified position belongs to.
+    /// The commit header is determined by the start and end regular
+    /// expressions defined in the `ContextFinder`, the file and the hunk by the
+    /// last file and hunk header before the position within that commit.
     ///
     /// # Arguments
     ///
@@ -47,21 +114,341 @@ impl ContextFinder {
     ///
     /// # Returns
     ///
-    /// * `Option<&'a [String]>` - Returns an Option containing a slice of strings representing the context if found, otherwise None.
-    pub fn get_context<'a>(
+    /// * `Vec<Context<'a>>` - The contexts from the outermost to the innermost level, empty if
+    ///   the position is not inside any context.
+    #[must_use]
+    pub fn get_contexts<'a>(&self, all_lines: &'a [String], position: usize) -> Vec<Context<'a>> {
+        trace!("Finding contexts");
+        let mut contexts = Vec::new();
+        let mut level_start = 0;
+        if let Some(range) = self.find_range(all_lines, position) {
+            level_start = range.start;
+            if let Some(lines) = all_lines.get(range.start..=range.end) {
+                let summary = self
+                    .parse_commit_header(all_lines, range.start)
+                    .map_or_else(|| lines[0].clone(), |commit| commit.summary());
+                contexts.push(Context {
+                    boundary: Boundary::Commit,
+                    lines,
+                    summary,
+                });
+            }
+        }
+        for boundary in [Boundary::File, Boundary::Hunk] {
+            match self.previous_start(boundary, all_lines, position) {
+                Some(start) if start >= level_start => {
+                    contexts.push(Context {
+                        boundary,
+                        lines: &all_lines[start..=start],
+                        summary: all_lines[start].clone(),
+                    });
+                    level_start = start;
+                }
+                _ => break,
+            }
+        }
+        contexts
+    }
+
+    /// Classifies a single line of the input for coloring.
+    #[must_use]
+    pub fn line_kind(&self, line: &str) -> LineKind {
+        if self.start.is_match(line) {
+            LineKind::Commit
+        } else if self.file_header.is_match(line) {
+            LineKind::FileHeader
+        } else if self.hunk.is_match(line) {
+            LineKind::Hunk
+        } else if line.starts_with('+') {
+            LineKind::Added
+        } else if line.starts_with('-') {
+            LineKind::Removed
+        } else {
+            LineKind::Other
+        }
+    }
+
+    /// Parses the commit the line at `position` belongs to, including the
+    /// files changed in it.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Commit>` - The commit, or None if the line is not inside a commit.
+    #[must_use]
+    pub fn get_commit(&self, all_lines: &[String], position: usize) -> Option<Commit> {
+        trace!("Parsing commit");
+        let start = self.previous_start(Boundary::Commit, all_lines, position + 1)?;
+        let end = self
+            .next_start(Boundary::Commit, all_lines, start)
+            .unwrap_or(all_lines.len());
+        let mut commit = self.parse_commit_header(all_lines, start)?;
+        commit.files = self.parse_files(all_lines, start..end);
+        Some(commit)
+    }
+
+    /// Parses the headers of all commits in `all_lines`.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Commit>` - The commits without their files, in the order of the input.
+    #[must_use]
+    pub fn get_commits(&self, all_lines: &[String]) -> Vec<Commit> {
+        trace!("Parsing commit headers");
+        all_lines
+            .iter()
+            .enumerate()
+            .filter(|(_line_num, line)| self.start.is_match(line))
+            .filter_map(|(line_num, _line)| self.parse_commit_header(all_lines, line_num))
+            .collect()
+    }
+
+    /// Parses the header of the commit starting at line `start` once it has
+    /// been read completely.
+    ///
+    /// The header is complete when it is followed by the first file diff or
+    /// the next commit, or when `at_end` tells that the whole input has been
+    /// read.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Commit>` - The commit without its files, or None if there is no commit
+    ///   starting at `start` or its header is not complete yet.
+    #[must_use]
+    pub fn get_commit_header(
         &self,
-        all_lines: &'a [String],
-        position: usize,
-    ) -> Option<&'a [String]> {
-        trace!("Finding context");
-        let context_lines = self.find_range(all_lines, position);
-        if let Some(lines) = context_lines {
-            all_lines.get(lines.start..=lines.end + 1)
+        all_lines: &[String],
+        start: usize,
+        at_end: bool,
+    ) -> Option<Commit> {
+        let complete = at_end
+            || all_lines
+                .iter()
+                .skip(start + 1)
+                .any(|line| self.end.is_match(line));
+        if complete {
+            self.parse_commit_header(all_lines, start)
         } else {
             None
         }
     }
 
+    /// Checks whether `line` starts a `boundary`.
+    #[must_use]
+    pub fn is_start(&self, boundary: Boundary, line: &str) -> bool {
+        self.boundary_regex(boundary).is_match(line)
+    }
+
+    /// Parses the header of the commit starting at line `start`, leaving the
+    /// list of files empty.
+    fn parse_commit_header(&self, all_lines: &[String], start: usize) -> Option<Commit> {
+        let hash = self.start.captures(all_lines.get(start)?)?.get(1)?.as_str();
+        let mut commit = Commit {
+            line: start,
+            hash: hash.to_string(),
+            ..Commit::default()
+        };
+        let mut lines = all_lines.iter().skip(start + 1);
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            } else if let Some(author) = line.strip_prefix("Author:") {
+                commit.author = author.trim().to_string();
+            } else if let Some(date) = line.strip_prefix("Date:") {
+                commit.date = date.trim().to_string();
+            }
+        }
+        let message: Vec<&str> = lines
+            .take_while(|line| line.is_empty() || line.starts_with("    "))
+            .map(|line| line.strip_prefix("    ").unwrap_or_default())
+            .collect();
+        commit.message = message.join("\n").trim_end().to_string();
+        Some(commit)
+    }
+
+    /// Line numbers of the first lines of the old and new versions in a hunk,
+    /// if `line` is a hunk header such as `@@ -12,7 +12,8 @@`.
+    #[must_use]
+    pub fn hunk_start_lines(&self, line: &str) -> Option<(usize, usize)> {
+        let captures = self.hunk.captures(line)?;
+        Some((
+            captures.get(1)?.as_str().parse().ok()?,
+            captures.get(3)?.as_str().parse().ok()?,
+        ))
+    }
+
+    /// First lines of the file diff and the hunk the line at `position`
+    /// belongs to.
+    ///
+    /// # Returns
+    /// * `(Option<usize>, Option<usize>)` - The line numbers of the starts of
+    ///   the file diff and the hunk, or None when the line is not inside one.
+    #[must_use]
+    pub fn section_starts(
+        &self,
+        all_lines: &[String],
+        position: usize,
+    ) -> (Option<usize>, Option<usize>) {
+        let commit_start = self.previous_start(Boundary::Commit, all_lines, position + 1);
+        let file_start = self
+            .previous_start(Boundary::File, all_lines, position + 1)
+            .filter(|start| commit_start.is_none_or(|commit_start| *start > commit_start));
+        let hunk_start = self
+            .previous_start(Boundary::Hunk, all_lines, position + 1)
+            .filter(|start| file_start.is_some_and(|file_start| *start > file_start));
+        (file_start, hunk_start)
+    }
+
+    /// The path of the file after the change, if `line` starts a file diff.
+    #[must_use]
+    pub fn file_path<'a>(&self, line: &'a str) -> Option<&'a str> {
+        self.diff_paths(line).map(|(_old_path, new_path)| new_path)
+    }
+
+    /// Parses the paths before and after the change from a `diff --git` line.
+    ///
+    /// The paths of an unrenamed file are told apart by being the same even
+    /// when they contain ` b/`, otherwise the line is split at the first
+    /// ` b/`. `parse_files` corrects the paths from the `---` and `+++`
+    /// headers following the line.
+    fn diff_paths<'a>(&self, line: &'a str) -> Option<(&'a str, &'a str)> {
+        if !self.file.is_match(line) {
+            return None;
+        }
+        let paths = line.trim_start_matches("diff --git ");
+        let half = paths.len() / 2;
+        if let (Some(old_path), Some(new_path)) = (
+            paths.get(..half).and_then(|path| path.strip_prefix("a/")),
+            paths.get(half..).and_then(|path| path.strip_prefix(" b/")),
+        ) {
+            if old_path == new_path {
+                return Some((old_path, new_path));
+            }
+        }
+        let (old_path, new_path) = paths.split_once(" b/").unwrap_or((paths, paths));
+        Some((old_path.trim_start_matches("a/"), new_path))
+    }
+
+    /// Numbers of the lines of the old and new versions in a hunk, if `line`
+    /// is a hunk header such as `@@ -12,7 +12,8 @@`. A count left out of the
+    /// header is one.
+    #[must_use]
+    pub fn hunk_line_counts(&self, line: &str) -> Option<(usize, usize)> {
+        let captures = self.hunk.captures(line)?;
+        let count = |index| {
+            captures.get(index).map_or(Some(1), |count: regex::Match| {
+                count.as_str().trim_start_matches(',').parse().ok()
+            })
+        };
+        Some((count(2)?, count(4)?))
+    }
+
+    /// Parses the file diffs within the lines in `range`.
+    ///
+    /// The lines of a hunk are counted up to the numbers of lines given in
+    /// its header, so that lines following the last hunk, such as the `-- `
+    /// signature of `git format-patch`, are not counted as changes.
+    fn parse_files(&self, all_lines: &[String], range: Range<usize>) -> Vec<FileDiff> {
+        let mut files: Vec<FileDiff> = Vec::new();
+        // Numbers of the old and new lines left in the current hunk.
+        let mut left: (usize, usize) = (0, 0);
+        let start = range.start;
+        for (line_num, line) in all_lines.get(range).unwrap_or_default().iter().enumerate() {
+            let (old_left, new_left) = &mut left;
+            let counted = match (line.chars().next(), files.last_mut()) {
+                _ if (*old_left, *new_left) == (0, 0) => false,
+                (Some('+'), Some(file)) if *new_left > 0 => {
+                    file.added += 1;
+                    *new_left -= 1;
+                    true
+                }
+                (Some('-'), Some(file)) if *old_left > 0 => {
+                    file.removed += 1;
+                    *old_left -= 1;
+                    true
+                }
+                (None | Some(' '), _) => {
+                    *old_left = old_left.saturating_sub(1);
+                    *new_left = new_left.saturating_sub(1);
+                    true
+                }
+                (Some('\\'), _) => true,
+                _ => {
+                    // The hunk is shorter than its header tells.
+                    (*old_left, *new_left) = (0, 0);
+                    false
+                }
+            };
+            if counted {
+                continue;
+            }
+            if let Some((old_path, new_path)) = self.diff_paths(line) {
+                files.push(FileDiff {
+                    line: start + line_num,
+                    old_path: old_path.to_string(),
+                    new_path: new_path.to_string(),
+                    ..FileDiff::default()
+                });
+            } else if let Some(counts) = self.hunk_line_counts(line) {
+                left = counts;
+            } else if let Some(file) = files.last_mut() {
+                if let Some(path) = line.strip_prefix("--- a/") {
+                    file.old_path = path.to_string();
+                } else if let Some(path) = line.strip_prefix("+++ b/") {
+                    file.new_path = path.to_string();
+                }
+            }
+        }
+        files
+    }
+
+    /// Finds the line number of the first `boundary` after `position`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The line number of the next boundary, or None if there is no such
+    ///   boundary after `position`.
+    #[must_use]
+    pub fn next_start(
+        &self,
+        boundary: Boundary,
+        all_lines: &[String],
+        position: usize,
+    ) -> Option<usize> {
+        trace!("Looking for next {boundary:?} start line");
+        let regex = self.boundary_regex(boundary);
+        all_lines
+            .iter()
+            .enumerate()
+            .skip(position + 1)
+            .find(|(_line_num, line)| regex.is_match(line))
+            .map(|(num, _line)| num)
+    }
+
+    /// Finds the line number of the last `boundary` before `position`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The line number of the previous boundary, or None if there is no such
+    ///   boundary before `position`.
+    #[must_use]
+    pub fn previous_start(
+        &self,
+        boundary: Boundary,
+        all_lines: &[String],
+        position: usize,
+    ) -> Option<usize> {
+        trace!("Looking for previous {boundary:?} start line");
+        last_match_before(self.boundary_regex(boundary), all_lines, position)
+    }
+
+    fn boundary_regex(&self, boundary: Boundary) -> &Regex {
+        match boundary {
+            Boundary::Commit => &self.start,
+            Boundary::File => &self.file,
+            Boundary::Hunk => &self.hunk,
+        }
+    }
+
     fn find_range(&self, lines: &[String], current_position: usize) -> Option<Range<usize>> {
         if let Some(context_start_position) = self.start_line_num(lines, current_position) {
             if let Some(context_end_delta) =
@@ -84,14 +471,7 @@ impl ContextFinder {
 
     fn start_line_num(&self, lines: &[String], start_position: usize) -> Option<usize> {
         trace!("Looking for start line");
-        let pos = lines.get(0..start_position).map(|lines| {
-            lines
-                .iter()
-                .enumerate()
-                .rev()
-                .find(|(_line_num, line)| self.start.is_match(line))
-        });
-        pos.unwrap_or(None).map(|(num, _line)| num)
+        last_match_before(&self.start, lines, start_position)
     }
 
     fn end_line_num(
@@ -113,11 +493,25 @@ impl ContextFinder {
     }
 }
 
+fn last_match_before(regex: &Regex, lines: &[String], position: usize) -> Option<usize> {
+    let pos = lines.get(0..position).map(|lines| {
+        lines
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_line_num, line)| regex.is_match(line))
+    });
+    pos.unwrap_or(None).map(|(num, _line)| num)
+}
+
 #[cfg(test)]
 mod test {
     use std::io::BufRead;
 
-    use crate::{context_finder::ContextFinder, error::Error};
+    use crate::{
+        context_finder::{Boundary, ContextFinder, LineKind},
+        error::Error,
+    };
 
     pub const GIT_LOG: &str = include_str!("../tests/data/git_patch");
 
@@ -177,6 +571,193 @@ mod test {
         assert!(input[range.start + 1].contains("Mr. Example"));
     }
 
+    #[test]
+    fn next_commit_start() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        assert_eq!(cf.next_start(Boundary::Commit, &input, 0), Some(178));
+        assert_eq!(cf.next_start(Boundary::Commit, &input, 100), Some(178));
+        assert_eq!(cf.next_start(Boundary::Commit, &input, 178), Some(306));
+        assert_eq!(cf.next_start(Boundary::Commit, &input, 306), None);
+    }
+
+    #[test]
+    fn previous_commit_start() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        assert_eq!(
+            cf.previous_start(Boundary::Commit, &input, input.len() - 1),
+            Some(306)
+        );
+        assert_eq!(cf.previous_start(Boundary::Commit, &input, 306), Some(178));
+        assert_eq!(cf.previous_start(Boundary::Commit, &input, 100), Some(0));
+        assert_eq!(cf.previous_start(Boundary::Commit, &input, 0), None);
+    }
+
+    #[test]
+    fn next_file_and_hunk_start() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        assert_eq!(cf.next_start(Boundary::File, &input, 0), Some(6));
+        assert_eq!(cf.next_start(Boundary::File, &input, 6), Some(18));
+        assert_eq!(cf.next_start(Boundary::Hunk, &input, 6), Some(10));
+        assert_eq!(cf.next_start(Boundary::Hunk, &input, 10), Some(22));
+        assert_eq!(cf.next_start(Boundary::Hunk, &input, 334), None);
+    }
+
+    #[test]
+    fn previous_file_and_hunk_start() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        assert_eq!(cf.previous_start(Boundary::File, &input, 184), Some(18));
+        assert_eq!(cf.previous_start(Boundary::Hunk, &input, 188), Some(52));
+        assert_eq!(cf.previous_start(Boundary::Hunk, &input, 10), None);
+    }
+
+    #[test]
+    fn nested_contexts() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        let contexts = cf.get_contexts(&input, 30);
+        let boundaries: Vec<_> = contexts.iter().map(|c| c.boundary).collect();
+        assert_eq!(
+            boundaries,
+            vec![Boundary::Commit, Boundary::File, Boundary::Hunk]
+        );
+        assert_eq!(contexts[0].lines, &input[0..=5]);
+        assert_eq!(contexts[1].lines, &input[18..=18]);
+        assert_eq!(contexts[2].lines, &input[22..=22]);
+    }
+
+    #[test]
+    fn nested_contexts_skip_previous_commit() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        let contexts = cf.get_contexts(&input, 180);
+        assert_eq!(contexts.len(), 1);
+        assert_eq!(contexts[0].lines, &input[178..=179]);
+        let contexts = cf.get_contexts(&input, 186);
+        assert_eq!(contexts.len(), 2);
+        assert_eq!(contexts[1].boundary, Boundary::File);
+        assert_eq!(contexts[1].lines, &input[184..=184]);
+    }
+
+    #[test]
+    fn parse_commit() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        let commit = cf.get_commit(&input, 30).unwrap();
+        assert_eq!(commit.line, 0);
+        assert_eq!(commit.hash, "b8e882d50a8e2f184e8803a18818da18dbbd1469");
+        assert_eq!(commit.author, "Mr. Example <mr@example.com.com>");
+        assert_eq!(commit.date, "Wed Apr 12 17:49:27 2023 +0300");
+        assert_eq!(commit.message, "Read stdin");
+        let files: Vec<_> = commit
+            .files
+            .iter()
+            .map(|file| (file.line, file.path(), file.added, file.removed))
+            .collect();
+        assert_eq!(
+            files,
+            vec![(6, "Cargo.toml", 2, 1), (18, "src/main.rs", 63, 63)]
+        );
+        assert_eq!(cf.get_commit(&input, 178).unwrap().line, 178);
+        assert_eq!(cf.get_commit(&input, 177).unwrap().line, 0);
+    }
+
+    #[test]
+    fn parse_files_by_headers_and_hunk_lengths() {
+        let input: Vec<String> = [
+            "diff --git a/docs/a b/c.md b/docs/a b/c.md",
+            "--- a/docs/a b/c.md",
+            "+++ b/docs/a b/c.md",
+            "@@ -1,2 +1,2 @@",
+            "-old",
+            "+new",
+            " same",
+            "diff --git a/x b/y b/x b/z",
+            "similarity index 90%",
+            "rename from x b/y",
+            "rename to x b/z",
+            "--- a/x b/y",
+            "+++ b/x b/z",
+            "@@ -3 +3 @@",
+            "-a",
+            "+b",
+            "-- ",
+            "2.43.0",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        let files: Vec<_> = cf
+            .parse_files(&input, 0..input.len())
+            .into_iter()
+            .map(|file| (file.old_path, file.new_path, file.added, file.removed))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("docs/a b/c.md".into(), "docs/a b/c.md".into(), 1, 1),
+                ("x b/y".into(), "x b/z".into(), 1, 1),
+            ]
+        );
+        assert_eq!(cf.hunk_line_counts("@@ -3 +3,0 @@"), Some((1, 0)));
+    }
+
+    #[test]
+    fn all_commits() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        let commits = cf.get_commits(&input);
+        assert_eq!(
+            commits.iter().map(|commit| commit.line).collect::<Vec<_>>(),
+            vec![0, 178, 306]
+        );
+        assert_eq!(commits[0].subject(), "Read stdin");
+    }
+
+    #[test]
+    fn commit_header_complete() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        assert!(cf.get_commit_header(&input[..5], 0, false).is_none());
+        let commit = cf.get_commit_header(&input[..5], 0, true).unwrap();
+        assert_eq!(commit.message, "Read stdin");
+        assert!(commit.files.is_empty());
+        assert!(cf.get_commit_header(&input, 0, false).is_some());
+        assert!(cf.get_commit_header(&input, 1, true).is_none());
+    }
+
+    #[test]
+    fn line_kinds() {
+        let input: Vec<String> = GIT_LOG.lines().map(ToString::to_string).collect();
+        let cf = ContextFinder::new(&crate::context_finder::InputType::Git).unwrap();
+        let kinds: Vec<_> = input[0..13].iter().map(|line| cf.line_kind(line)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                LineKind::Commit,
+                LineKind::Other,
+                LineKind::Other,
+                LineKind::Other,
+                LineKind::Other,
+                LineKind::Other,
+                LineKind::FileHeader,
+                LineKind::FileHeader,
+                LineKind::FileHeader,
+                LineKind::FileHeader,
+                LineKind::Hunk,
+                LineKind::Other,
+                LineKind::Other,
+            ]
+        );
+        assert_eq!(cf.line_kind("+ratatui = \"0.20.1\""), LineKind::Added);
+        assert_eq!(cf.line_kind("-tui = \"0.19.0\""), LineKind::Removed);
+        assert_eq!(cf.line_kind("--- removed sql comment"), LineKind::Removed);
+    }
+
     #[test]
     fn find_commit_patch() {
         let lines = GIT_LOG.lines();
@@ -1,18 +1,24 @@
-use std::array::TryFromSliceError;
 use std::io;
 use std::num::TryFromIntError;
+use std::path::PathBuf;
 use std::sync::mpsc;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Could not value to type: {0}")]
     Cast(#[from] TryFromIntError),
+    #[error("Unknown command: {0}")]
+    Command(String),
     #[error("Could not get lines to display")]
     GetLines,
-    #[error("Could not hilight search term")]
-    Hilight(#[from] TryFromSliceError),
+    #[error("Invalid glob pattern: {0}")]
+    Glob(#[from] globset::Error),
     #[error("Could not initialize terminal")]
     Io(#[from] io::Error),
+    #[error("Could not open {}", path.display())]
+    OpenInput { path: PathBuf, source: io::Error },
+    #[error("Could not parse date: {0}")]
+    ParseDate(#[from] chrono::ParseError),
     #[error("Could not parse regular expression")]
     RegexBuild(#[from] regex::Error),
     #[error("Error with search term")]
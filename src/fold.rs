@@ -0,0 +1,112 @@
+use crate::context_finder::Boundary;
+use crate::error::Error;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::BTreeSet;
+
+/// Summary of a folded file diff or hunk, shown in place of its lines.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fold {
+    pub boundary: Boundary,
+    /// Number of the lines added in the folded section read so far.
+    pub added: usize,
+    /// Number of the lines removed in the folded section read so far.
+    pub removed: usize,
+}
+
+/// The file diffs and hunks to show folded.
+///
+/// File diffs whose path matches one of the globs are folded. Files and hunks
+/// can also be folded or unfolded one by one, overriding the globs.
+#[derive(Clone, Debug, Default)]
+pub struct Folds {
+    patterns: Vec<String>,
+    globs: GlobSet,
+    /// First lines of the sections toggled one by one.
+    toggled: BTreeSet<usize>,
+}
+
+impl Folds {
+    /// Folds the files whose path matches `pattern`, such as `*.lock` or
+    /// `vendor/**`.
+    ///
+    /// # Errors
+    /// This function can return errors in the following cases:
+    /// * If the pattern is not a valid glob
+    pub fn add_glob(&mut self, pattern: &str) -> Result<(), Error> {
+        Glob::new(pattern)?;
+        if !self.patterns.iter().any(|existing| existing == pattern) {
+            self.patterns.push(pattern.to_string());
+            self.build_globs()?;
+        }
+        Ok(())
+    }
+
+    /// Stops folding the files matching `pattern` added with `add_glob`.
+    ///
+    /// # Errors
+    /// This function can return errors in the following cases:
+    /// * If the remaining patterns can not be compiled
+    pub fn remove_glob(&mut self, pattern: &str) -> Result<(), Error> {
+        self.patterns.retain(|existing| existing != pattern);
+        self.build_globs()
+    }
+
+    /// Unfolds everything.
+    pub fn clear(&mut self) {
+        *self = Folds::default();
+    }
+
+    /// Folds the section starting at line `line_num` if it is unfolded, and
+    /// unfolds it otherwise.
+    pub fn toggle(&mut self, line_num: usize) {
+        if !self.toggled.remove(&line_num) {
+            self.toggled.insert(line_num);
+        }
+    }
+
+    /// Checks whether the section starting at line `line_num` is folded.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file if the section is a file diff.
+    #[must_use]
+    pub fn is_folded(&self, line_num: usize, path: Option<&str>) -> bool {
+        path.is_some_and(|path| self.globs.is_match(path)) != self.toggled.contains(&line_num)
+    }
+
+    fn build_globs(&mut self) -> Result<(), Error> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.patterns {
+            builder.add(Glob::new(pattern)?);
+        }
+        self.globs = builder.build()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Folds;
+
+    #[test]
+    fn fold_by_glob_and_toggle() {
+        let mut folds = Folds::default();
+        folds.add_glob("*.lock").unwrap();
+        folds.add_glob("vendor/**").unwrap();
+        assert!(folds.is_folded(10, Some("Cargo.lock")));
+        assert!(folds.is_folded(20, Some("sub/dir/Cargo.lock")));
+        assert!(folds.is_folded(30, Some("vendor/crate/src/lib.rs")));
+        assert!(!folds.is_folded(40, Some("src/main.rs")));
+        assert!(!folds.is_folded(50, None));
+        folds.toggle(10);
+        folds.toggle(50);
+        assert!(!folds.is_folded(10, Some("Cargo.lock")));
+        assert!(folds.is_folded(50, None));
+        folds.toggle(50);
+        assert!(!folds.is_folded(50, None));
+        folds.remove_glob("*.lock").unwrap();
+        assert!(!folds.is_folded(20, Some("sub/dir/Cargo.lock")));
+        assert!(folds.add_glob("a[").is_err());
+        folds.clear();
+        assert!(!folds.is_folded(30, Some("vendor/crate/src/lib.rs")));
+    }
+}
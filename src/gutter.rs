@@ -0,0 +1,390 @@
+use crate::context_finder::{Boundary, ContextFinder};
+use itertools::Itertools;
+
+/// Maximum number of lines gone through from the previous row to the next one
+/// before looking up the hunk of the next row from scratch.
+const MAX_WALK: usize = 1000;
+/// Minimum number of digits of the line numbers of the input, so that the
+/// gutter does not widen while the input is read.
+const MIN_BUFFER_WIDTH: usize = 6;
+/// Minimum number of digits of the line numbers of the changed files, so that
+/// the gutter keeps its width when scrolling through files of up to 9999
+/// lines.
+const MIN_DIFF_WIDTH: usize = 4;
+
+/// Line numbers shown in the gutter left of the lines.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum GutterMode {
+    /// No gutter
+    #[default]
+    None,
+    /// Line numbers of the input
+    Buffer,
+    /// Line numbers of the old and new versions of the changed files
+    Diff,
+}
+
+impl GutterMode {
+    /// The mode after this one when cycling through the modes.
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            GutterMode::None => GutterMode::Buffer,
+            GutterMode::Buffer => GutterMode::Diff,
+            GutterMode::Diff => GutterMode::None,
+        }
+    }
+}
+
+/// Line numbers of the old and new versions of a line in a hunk.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DiffLineNumbers {
+    pub old: Option<usize>,
+    pub new: Option<usize>,
+}
+
+/// Texts of the gutter of a row.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Gutter {
+    /// Gutter of the row shown across the screen.
+    pub unified: String,
+    /// Gutter of the row shown in the left column of the side-by-side mode.
+    pub old: String,
+    /// Gutter of the row shown in the right column of the side-by-side mode.
+    pub new: String,
+}
+
+/// Position in a hunk while going through its lines.
+struct HunkPosition {
+    /// Line number in the input of the last line gone through.
+    line_num: usize,
+    /// Line number in the old version of the next context or removed line.
+    old: usize,
+    /// Line number in the new version of the next context or added line.
+    new: usize,
+    /// Number of lines of the old version left in the hunk.
+    old_left: usize,
+    /// Number of lines of the new version left in the hunk.
+    new_left: usize,
+}
+
+impl HunkPosition {
+    /// Starts at the hunk header at line `line_num`.
+    fn start(lines: &[String], line_num: usize, cf: &ContextFinder) -> Option<Self> {
+        let line = lines.get(line_num)?;
+        let (old, new) = cf.hunk_start_lines(line)?;
+        let (old_left, new_left) = cf.hunk_line_counts(line)?;
+        Some(HunkPosition {
+            line_num,
+            old,
+            new,
+            old_left,
+            new_left,
+        })
+    }
+
+    /// Goes through the lines up to line `line_num`, continuing into the
+    /// following hunks of the same file.
+    ///
+    /// # Returns
+    /// * `Option<DiffLineNumbers>` - The line numbers of the line, or None if
+    ///   the hunks end before it.
+    fn walk_to(
+        &mut self,
+        lines: &[String],
+        line_num: usize,
+        cf: &ContextFinder,
+    ) -> Option<DiffLineNumbers> {
+        let mut numbers = DiffLineNumbers::default();
+        while self.line_num < line_num {
+            if let Some(next) = HunkPosition::start(lines, self.line_num + 1, cf) {
+                *self = next;
+                numbers = DiffLineNumbers::default();
+                continue;
+            }
+            self.line_num += 1;
+            let (has_old, has_new) = (self.old_left > 0, self.new_left > 0);
+            numbers = match lines.get(self.line_num)?.chars().next() {
+                Some('\\') => DiffLineNumbers::default(),
+                None | Some(' ') if has_old && has_new => DiffLineNumbers {
+                    old: Some(self.old),
+                    new: Some(self.new),
+                },
+                Some('-') if has_old => DiffLineNumbers {
+                    old: Some(self.old),
+                    new: None,
+                },
+                Some('+') if has_new => DiffLineNumbers {
+                    old: None,
+                    new: Some(self.new),
+                },
+                // The hunk ended before the line, such as at the blank line
+                // before the next commit.
+                _ => return None,
+            };
+            if numbers.old.is_some() {
+                self.old += 1;
+                self.old_left -= 1;
+            }
+            if numbers.new.is_some() {
+                self.new += 1;
+                self.new_left -= 1;
+            }
+        }
+        Some(numbers)
+    }
+}
+
+/// Finds the line numbers in the old and new versions of the changed files of
+/// `rows`, going through each hunk from its header.
+///
+/// # Returns
+/// * `Vec<DiffLineNumbers>` - The line numbers of each row, none for the rows
+///   outside hunks.
+#[must_use]
+pub fn diff_line_numbers(
+    lines: &[String],
+    rows: &[usize],
+    cf: &ContextFinder,
+) -> Vec<DiffLineNumbers> {
+    let mut position: Option<HunkPosition> = None;
+    rows.iter()
+        .map(|line_num| {
+            // Continue from the previous row when it is close enough.
+            if let Some(numbers) = position
+                .as_mut()
+                .filter(|position| {
+                    line_num
+                        .checked_sub(position.line_num)
+                        .is_some_and(|gap| gap <= MAX_WALK)
+                })
+                .and_then(|position| position.walk_to(lines, *line_num, cf))
+            {
+                return numbers;
+            }
+            position = cf
+                .section_starts(lines, *line_num)
+                .1
+                .and_then(|hunk_start| HunkPosition::start(lines, hunk_start, cf));
+            let numbers = position
+                .as_mut()
+                .and_then(|position| position.walk_to(lines, *line_num, cf));
+            if numbers.is_none() {
+                position = None;
+            }
+            numbers.unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Finds the first line of the file diff of each of `rows`, going through the
+/// lines from the previous row when it is close enough.
+fn file_starts(lines: &[String], rows: &[usize], cf: &ContextFinder) -> Vec<Option<usize>> {
+    let mut previous: Option<(usize, Option<usize>)> = None;
+    rows.iter()
+        .map(|line_num| {
+            let file_start = match previous {
+                Some((previous_line, file_start))
+                    if *line_num >= previous_line
+                        && line_num - previous_line <= MAX_WALK
+                        && lines
+                            .get(previous_line + 1..=*line_num)
+                            .unwrap_or_default()
+                            .iter()
+                            .all(|line| {
+                                !cf.is_start(Boundary::File, line)
+                                    && !cf.is_start(Boundary::Commit, line)
+                            }) =>
+                {
+                    file_start
+                }
+                _ => cf.section_starts(lines, *line_num).0,
+            };
+            previous = Some((*line_num, file_start));
+            file_start
+        })
+        .collect()
+}
+
+/// Number of digits of the largest line number in the hunk headers of the file
+/// diff starting at line `file_start`.
+fn diff_width(lines: &[String], file_start: usize, cf: &ContextFinder) -> usize {
+    lines
+        .iter()
+        .skip(file_start + 1)
+        .take_while(|line| {
+            !cf.is_start(Boundary::File, line) && !cf.is_start(Boundary::Commit, line)
+        })
+        .filter_map(|line| {
+            let (old, new) = cf.hunk_start_lines(line)?;
+            let (old_count, new_count) = cf.hunk_line_counts(line)?;
+            Some((old + old_count).max(new + new_count))
+        })
+        .max()
+        .unwrap_or_default()
+        .to_string()
+        .len()
+}
+
+/// Texts of the gutter of `rows`.
+///
+/// All gutters have the same width. The line numbers of the input are padded
+/// to the width of the number of lines and the line numbers of the changed
+/// files to the width of the largest line number in the hunk headers of the
+/// file diffs of `rows`, both with a minimum so that the gutter keeps its
+/// width when scrolling.
+///
+/// # Returns
+/// * `Vec<Gutter>` - The gutter of each row, or no gutters with
+///   `GutterMode::None`.
+#[must_use]
+pub fn gutters(
+    mode: GutterMode,
+    lines: &[String],
+    rows: &[usize],
+    cf: &ContextFinder,
+) -> Vec<Gutter> {
+    match mode {
+        GutterMode::None => Vec::new(),
+        GutterMode::Buffer => {
+            let width = lines.len().to_string().len().max(MIN_BUFFER_WIDTH);
+            rows.iter()
+                .map(|line_num| {
+                    let text = format!("{:>width$} ", line_num + 1);
+                    Gutter {
+                        unified: text.clone(),
+                        old: text.clone(),
+                        new: text,
+                    }
+                })
+                .collect()
+        }
+        GutterMode::Diff => {
+            let width = file_starts(lines, rows, cf)
+                .into_iter()
+                .flatten()
+                .unique()
+                .map(|file_start| diff_width(lines, file_start, cf))
+                .fold(MIN_DIFF_WIDTH, usize::max);
+            diff_line_numbers(lines, rows, cf)
+                .into_iter()
+                .map(|numbers| {
+                    let format = |number: Option<usize>| {
+                        number.map_or_else(
+                            || " ".repeat(width + 1),
+                            |number| format!("{number:>width$} "),
+                        )
+                    };
+                    let (old, new) = (format(numbers.old), format(numbers.new));
+                    Gutter {
+                        unified: format!("{old}{new}"),
+                        old,
+                        new,
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff_line_numbers, gutters, DiffLineNumbers, GutterMode};
+    use crate::context_finder::{ContextFinder, InputType};
+
+    fn lines() -> Vec<String> {
+        [
+            "diff --git a/src/parser.rs b/src/parser.rs",
+            "--- a/src/parser.rs",
+            "+++ b/src/parser.rs",
+            "@@ -410,3 +410,4 @@ fn parse() {",
+            "     let a = 1;",
+            "-    let b = 2;",
+            "+    let b = 3;",
+            "+    let c = 4;",
+            "     a + b",
+            "@@ -500 +501 @@",
+            "-x",
+            "\\ No newline at end of file",
+            "",
+            "commit 0123456789012345678901234567890123456789",
+            "diff --git a/b.rs b/b.rs",
+            "@@ -1 +1 @@",
+            "-y",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+    }
+
+    fn numbers(old: Option<usize>, new: Option<usize>) -> DiffLineNumbers {
+        DiffLineNumbers { old, new }
+    }
+
+    #[test]
+    fn line_numbers_of_hunks() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let lines = lines();
+        let all: Vec<usize> = (0..lines.len()).collect();
+        assert_eq!(
+            diff_line_numbers(&lines, &all, &cf)[3..14],
+            [
+                numbers(None, None),
+                numbers(Some(410), Some(410)),
+                numbers(Some(411), None),
+                numbers(None, Some(411)),
+                numbers(None, Some(412)),
+                numbers(Some(412), Some(413)),
+                numbers(None, None),
+                numbers(Some(500), None),
+                numbers(None, None),
+                numbers(None, None),
+                numbers(None, None),
+            ]
+        );
+        assert_eq!(
+            diff_line_numbers(&lines, &[0, 7, 10, 12], &cf),
+            [
+                numbers(None, None),
+                numbers(None, Some(412)),
+                numbers(Some(500), None),
+                numbers(None, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn gutter_texts() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let lines = lines();
+        assert!(gutters(GutterMode::None, &lines, &[0, 1], &cf).is_empty());
+        let buffer: Vec<_> = gutters(GutterMode::Buffer, &lines, &[0, 9], &cf)
+            .into_iter()
+            .map(|gutter| gutter.unified)
+            .collect();
+        assert_eq!(buffer, ["     1 ", "    10 "]);
+        let diff = gutters(GutterMode::Diff, &lines, &[3, 5, 6, 13, 16], &cf);
+        let unified: Vec<_> = diff.iter().map(|gutter| gutter.unified.as_str()).collect();
+        assert_eq!(
+            unified,
+            [
+                "          ",
+                " 411      ",
+                "      411 ",
+                "          ",
+                "   1      "
+            ]
+        );
+        assert_eq!(diff[1].old, " 411 ");
+        assert_eq!(diff[1].new, "     ");
+        assert_eq!(diff[2].new, " 411 ");
+        let wide: Vec<_> = ["diff --git a/c.rs b/c.rs", "@@ -99990,2 +99990,3 @@", " x"]
+            .into_iter()
+            .map(ToString::to_string)
+            .chain(lines)
+            .collect();
+        let diff = gutters(GutterMode::Diff, &wide, &[2, 8, 16], &cf);
+        assert!(diff.iter().all(|gutter| gutter.unified.len() == 12));
+        assert_eq!(diff[0].unified, "99990 99990 ");
+    }
+}
@@ -0,0 +1,187 @@
+use ratatui::style::Style;
+use ratatui::text::Span;
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthChar;
+
+/// Patches `style` onto the parts of `spans` that fall inside `ranges`.
+///
+/// The ranges are byte offsets into the concatenated text of the spans and
+/// must be sorted and non-overlapping. Spans are split at the range
+/// boundaries so that the style of the text outside the ranges is kept as is.
+#[must_use]
+pub fn overlay<'a>(spans: Vec<Span<'a>>, ranges: &[(usize, usize)], style: Style) -> Vec<Span<'a>> {
+    if ranges.is_empty() {
+        return spans;
+    }
+    let mut result = Vec::with_capacity(spans.len() + ranges.len() * 2);
+    let mut offset = 0;
+    for span in spans {
+        let span_start = offset;
+        let span_end = offset + span.content.len();
+        offset = span_end;
+        let mut cuts = vec![span_start, span_end];
+        cuts.extend(
+            ranges
+                .iter()
+                .flat_map(|(start, end)| [*start, *end])
+                .filter(|cut| *cut > span_start && *cut < span_end),
+        );
+        cuts.sort_unstable();
+        cuts.dedup();
+        for piece in cuts.windows(2) {
+            let (start, end) = (piece[0], piece[1]);
+            let Some(content) = sub_content(&span.content, start - span_start, end - span_start)
+            else {
+                continue;
+            };
+            let inside = ranges
+                .iter()
+                .any(|(range_start, range_end)| start >= *range_start && end <= *range_end);
+            let piece_style = if inside {
+                span.style.patch(style)
+            } else {
+                span.style
+            };
+            result.push(Span::styled(content, piece_style));
+        }
+    }
+    result
+}
+
+/// Cuts `spans` to `width` columns of the terminal, padding them with spaces
+/// to exactly `width` columns.
+#[must_use]
+pub fn fit(spans: Vec<Span<'_>>, width: usize) -> Vec<Span<'_>> {
+    let (mut result, _rest) = split_at_column(spans, width);
+    let used: usize = result.iter().map(Span::width).sum();
+    if used < width {
+        result.push(Span::raw(" ".repeat(width - used)));
+    }
+    result
+}
+
+/// Drops the first `columns` columns of `spans`, scrolling them to the left.
+#[must_use]
+pub fn skip_columns(spans: Vec<Span<'_>>, columns: usize) -> Vec<Span<'_>> {
+    split_at_column(spans, columns).1
+}
+
+/// Wraps `spans` into lines of `width` columns.
+///
+/// # Returns
+/// * `Vec<Vec<Span>>` - The spans of each line, at least one line.
+#[must_use]
+pub fn wrap_columns(mut spans: Vec<Span<'_>>, width: usize) -> Vec<Vec<Span<'_>>> {
+    let mut lines = Vec::new();
+    loop {
+        let (mut line, mut rest) = split_at_column(spans, width.max(1));
+        if line.iter().all(|span| span.content.is_empty()) && !rest.is_empty() {
+            // Put a character wider than the line on a line of its own.
+            let first_width = rest
+                .iter()
+                .flat_map(|span| span.content.chars())
+                .find_map(UnicodeWidthChar::width)
+                .unwrap_or(1);
+            (line, rest) = split_at_column(rest, first_width);
+        }
+        lines.push(line);
+        if rest.iter().all(|span| span.content.is_empty()) {
+            return lines;
+        }
+        spans = rest;
+    }
+}
+
+/// Splits `spans` into the spans of the first `column` columns and the rest.
+/// A wide character crossing the column is left to the rest.
+fn split_at_column(spans: Vec<Span<'_>>, column: usize) -> (Vec<Span<'_>>, Vec<Span<'_>>) {
+    let mut left = Vec::with_capacity(spans.len());
+    let mut right = Vec::new();
+    let mut used = 0;
+    for span in spans {
+        if used > column || !right.is_empty() {
+            right.push(span);
+            continue;
+        }
+        let split = span.content.char_indices().find_map(|(offset, c)| {
+            used += c.width().unwrap_or_default();
+            (used > column).then_some(offset)
+        });
+        let Some(split) = split else {
+            left.push(span);
+            continue;
+        };
+        if let Some(content) = sub_content(&span.content, 0, split).filter(|c| !c.is_empty()) {
+            left.push(Span::styled(content, span.style));
+        }
+        if let Some(content) = sub_content(&span.content, split, span.content.len()) {
+            right.push(Span::styled(content, span.style));
+        }
+    }
+    (left, right)
+}
+
+/// Slices `content` keeping a borrowed content borrowed.
+fn sub_content<'a>(content: &Cow<'a, str>, start: usize, end: usize) -> Option<Cow<'a, str>> {
+    match content {
+        Cow::Borrowed(content) => content.get(start..end).map(Cow::Borrowed),
+        Cow::Owned(content) => content
+            .get(start..end)
+            .map(|content| Cow::Owned(content.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fit, overlay, skip_columns, wrap_columns};
+    use ratatui::style::{Color, Style};
+    use ratatui::text::Span;
+
+    #[test]
+    fn overlay_across_spans() {
+        let green = Style::new().fg(Color::Green);
+        let hilight = Style::new().bg(Color::Gray);
+        let spans = vec![Span::styled("+let ", green), Span::raw("foo = 1;")];
+        let result = overlay(spans, &[(1, 4), (5, 8)], hilight);
+        let pieces: Vec<_> = result
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style))
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                ("+", green),
+                ("let", green.patch(hilight)),
+                (" ", green),
+                ("foo", hilight),
+                (" = 1;", Style::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn fit_to_width() {
+        let red = Style::new().fg(Color::Red);
+        let spans = vec![Span::styled("-let ", red), Span::raw("größe = 1;")];
+        let pieces = |spans: Vec<Span>| -> Vec<String> {
+            spans.iter().map(|span| span.content.to_string()).collect()
+        };
+        assert_eq!(pieces(fit(spans.clone(), 8)), ["-let ", "grö"]);
+        assert_eq!(pieces(fit(spans.clone(), 16)), ["-let ", "größe = 1;", " "]);
+        assert_eq!(pieces(fit(vec![Span::raw("日本")], 3)), ["日", " "]);
+        assert_eq!(pieces(skip_columns(spans.clone(), 7)), ["öße = 1;"]);
+        assert_eq!(
+            pieces(skip_columns(spans.clone(), 20)),
+            Vec::<String>::new()
+        );
+        let lines: Vec<_> = wrap_columns(spans, 6).into_iter().map(pieces).collect();
+        assert_eq!(lines, [vec!["-let ", "g"], vec!["röße ="], vec![" 1;"]]);
+        assert_eq!(wrap_columns(vec![Span::raw("")], 6).len(), 1);
+    }
+
+    #[test]
+    fn overlay_without_ranges() {
+        let spans = vec![Span::raw("text")];
+        assert_eq!(overlay(spans.clone(), &[], Style::new()), spans);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{trace, warn};
+
+/// Maximum number of search terms kept in the history.
+pub const MAX_HISTORY_ENTRIES: usize = 500;
+
+const HISTORY_FILE_NAME: &str = "search_history";
+
+/// Search terms of this and earlier sessions, browsable from the search box.
+#[derive(Debug, Default)]
+pub struct SearchHistory {
+    /// Terms from the oldest to the newest.
+    entries: Vec<String>,
+    /// File the history is persisted to.
+    path: Option<PathBuf>,
+    /// Index of the entry being browsed, None when editing a new term.
+    cursor: Option<usize>,
+    /// The term being edited before browsing the history started.
+    draft: String,
+}
+
+impl SearchHistory {
+    /// Loads the history from `path`, or starts an empty history if the file
+    /// does not exist yet. Without a path the history is kept only in memory.
+    #[must_use]
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_deref()
+            .map(|path| match fs::read_to_string(path) {
+                Ok(content) => content.lines().map(ToString::to_string).collect(),
+                Err(err) => {
+                    trace!("Could not read search history {}: {err}", path.display());
+                    Vec::new()
+                }
+            })
+            .unwrap_or_default();
+        SearchHistory {
+            entries,
+            path,
+            ..SearchHistory::default()
+        }
+    }
+
+    /// Default location of the history file, `$XDG_STATE_HOME/cag/search_history`
+    /// falling back to `~/.local/state/cag/search_history`.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+        Some(state_home.join("cag").join(HISTORY_FILE_NAME))
+    }
+
+    /// Terms from the oldest to the newest.
+    #[must_use]
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Adds `term` as the newest entry, removing an earlier copy of it and the
+    /// oldest entries beyond `MAX_HISTORY_ENTRIES`, and saves the history.
+    ///
+    /// The terms saved meanwhile by other sessions are read back first so
+    /// that sessions running at the same time keep each other's terms.
+    pub fn add(&mut self, term: &str) {
+        self.cursor = None;
+        if term.is_empty() || term.contains('\n') {
+            return;
+        }
+        self.merge_saved();
+        self.entries.retain(|entry| entry != term);
+        self.entries.push(term.to_string());
+        let overflow = self.entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+        self.entries.drain(..overflow);
+        if let Err(err) = self.save() {
+            warn!("Could not save search history: {err}");
+        }
+    }
+
+    /// Moves to the next older entry.
+    ///
+    /// # Arguments
+    /// * `current` - The term being edited, restored when moving back past the
+    ///   newest entry.
+    ///
+    /// # Returns
+    /// * `Option<&str>` - The older entry, or None if there is none.
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let index = match self.cursor {
+            None => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+            Some(index) => index.checked_sub(1)?,
+        };
+        self.cursor = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Moves to the next newer entry, or back to the term being edited after
+    /// the newest one.
+    ///
+    /// # Returns
+    /// * `Option<&str>` - The newer entry or the edited term, or None if not
+    ///   browsing the history.
+    pub fn newer(&mut self) -> Option<&str> {
+        let index = self.cursor?;
+        if index + 1 < self.entries.len() {
+            self.cursor = Some(index + 1);
+            self.entries.get(index + 1).map(String::as_str)
+        } else {
+            self.cursor = None;
+            Some(&self.draft)
+        }
+    }
+
+    /// Stops browsing the history.
+    pub fn reset(&mut self) {
+        self.cursor = None;
+    }
+
+    /// Merges the entries saved in the history file with the entries in
+    /// memory, keeping the saved order and adding the entries not saved after
+    /// them.
+    fn merge_saved(&mut self) {
+        let saved = SearchHistory::load(self.path.clone()).entries;
+        if saved.is_empty() {
+            return;
+        }
+        let unsaved = self
+            .entries
+            .drain(..)
+            .filter(|entry| !saved.contains(entry))
+            .collect::<Vec<_>>();
+        self.entries = saved;
+        self.entries.extend(unsaved);
+    }
+
+    /// Writes the history to a temporary file renamed over the history file,
+    /// so that the file is never left partially written.
+    fn save(&self) -> Result<(), Error> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = PathBuf::from(temp_path);
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SearchHistory, MAX_HISTORY_ENTRIES};
+
+    #[test]
+    fn browse() {
+        let mut history = SearchHistory::default();
+        history.add("first");
+        history.add("second");
+        assert_eq!(history.older("draft"), Some("second"));
+        assert_eq!(history.older("second"), Some("first"));
+        assert_eq!(history.older("first"), None);
+        assert_eq!(history.newer(), Some("second"));
+        assert_eq!(history.newer(), Some("draft"));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn dedup_and_cap() {
+        let mut history = SearchHistory::default();
+        for i in 0..MAX_HISTORY_ENTRIES + 10 {
+            history.add(&format!("term {i}"));
+        }
+        history.add("term 100");
+        assert_eq!(history.entries().len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(history.entries()[0], "term 10");
+        assert_eq!(history.entries().last().unwrap(), "term 100");
+        assert_eq!(
+            history
+                .entries()
+                .iter()
+                .filter(|entry| *entry == "term 100")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn persist() {
+        let path = std::env::temp_dir()
+            .join(format!("cag-test-{}", std::process::id()))
+            .join("search_history");
+        let mut history = SearchHistory::load(Some(path.clone()));
+        history.add("fn main");
+        history.add("unwrap");
+        let history = SearchHistory::load(Some(path.clone()));
+        assert_eq!(history.entries(), ["fn main", "unwrap"]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn concurrent_sessions() {
+        let path = std::env::temp_dir()
+            .join(format!("cag-test-concurrent-{}", std::process::id()))
+            .join("search_history");
+        let mut first = SearchHistory::load(Some(path.clone()));
+        let mut second = SearchHistory::load(Some(path.clone()));
+        first.add("one");
+        second.add("two");
+        first.add("three");
+        assert_eq!(first.entries(), ["one", "two", "three"]);
+        let history = SearchHistory::load(Some(path.clone()));
+        assert_eq!(history.entries(), ["one", "two", "three"]);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
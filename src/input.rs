@@ -1,28 +1,116 @@
+use crate::ansi::{self, AnsiStyles};
 use crate::error::Error;
-use std::io::{stdin, BufRead};
+use std::fs::File;
+use std::io::{stdin, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{channel, Receiver};
 use std::thread::{self, JoinHandle};
 use tracing::{trace, warn};
 
-pub fn stream_input(num_lines: usize) -> (Receiver<Result<Vec<String>, Error>>, JoinHandle<()>) {
+/// Lines read from the input with ANSI escape sequences stripped.
+#[derive(Debug, Default)]
+pub struct Lines {
+    pub text: Vec<String>,
+    /// Styles set by the escape sequences of each line in `text`.
+    pub styles: Vec<AnsiStyles>,
+}
+
+impl Lines {
+    fn with_capacity(capacity: usize) -> Self {
+        Lines {
+            text: Vec::with_capacity(capacity),
+            styles: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn push(&mut self, line: &str) {
+        let (text, styles) = ansi::parse(line);
+        self.text.push(text);
+        self.styles.push(styles);
+    }
+
+    /// Appends the lines of `other` to these lines.
+    pub fn extend(&mut self, other: Lines) {
+        self.text.extend(other.text);
+        self.styles.extend(other.styles);
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+/// Path that stands for standard input when given as an input file.
+pub const STDIN_PATH: &str = "-";
+
+/// Opens the given input files as a single readable source.
+///
+/// The files are read one after the other in the order given. A path of `-`
+/// reads standard input in its place, and an empty list of paths reads only
+/// standard input.
+///
+/// # Errors
+/// This function can return errors in the following cases:
+/// * If any of the files can not be opened
+pub fn open_input(paths: &[PathBuf]) -> Result<Box<dyn Read + Send>, Error> {
+    if paths.is_empty() {
+        trace!("No input files given, reading stdin");
+        return Ok(Box::new(stdin()));
+    }
+    let mut input: Box<dyn Read + Send> = Box::new(std::io::empty());
+    for path in paths {
+        input = Box::new(input.chain(open_path(path)?));
+    }
+    Ok(input)
+}
+
+fn open_path(path: &Path) -> Result<Box<dyn Read + Send>, Error> {
+    if path.as_os_str() == STDIN_PATH {
+        trace!("Reading stdin");
+        Ok(Box::new(stdin()))
+    } else {
+        trace!("Opening input file {}", path.display());
+        let file = File::open(path).map_err(|source| Error::OpenInput {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(Box::new(file))
+    }
+}
+
+/// Streams lines from `input` in batches of `num_lines` lines.
+///
+/// The input is read in a separate thread so that the first screenful can be
+/// shown before the whole input has been read. ANSI escape sequences are
+/// stripped from the lines and returned as their styles.
+pub fn stream_input<R: Read + Send + 'static>(
+    input: R,
+    num_lines: usize,
+) -> (Receiver<Result<Lines, Error>>, JoinHandle<()>) {
     trace!("Opening channel for input reader");
-    let (tx, rx) = channel::<Result<Vec<String>, Error>>();
+    let (tx, rx) = channel::<Result<Lines, Error>>();
     let thread_handle = thread::spawn(move || {
         trace!("Reading input");
-        let input = stdin().lock();
+        let input = BufReader::new(input);
         trace!("Splitting input");
         let mut input_lines = input.split(b'\n');
 
         loop {
             trace!("Reading lines");
             let mut maybe_err = None;
-            let mut lines = Vec::with_capacity(num_lines);
+            let mut at_end = false;
+            let mut lines = Lines::with_capacity(num_lines);
             for _ in 0..num_lines {
                 match input_lines.next() {
                     Some(Ok(buf)) => {
                         trace!("Got lines");
-                        let line = String::from_utf8_lossy(&buf).to_string();
-                        lines.push(line);
+                        lines.push(&String::from_utf8_lossy(&buf));
                     }
                     Some(Err(err)) => {
                         warn!("Error reading input lines: {err}");
@@ -31,12 +119,18 @@ pub fn stream_input(num_lines: usize) -> (Receiver<Result<Vec<String>, Error>>,
                     }
                     None => {
                         trace!("No new lines");
-                        return;
+                        at_end = true;
+                        break;
                     }
                 }
             }
-            if let Err(err) = tx.send(Ok(lines)) {
-                warn!("Error sending input streaming result: {err}");
+            if !lines.is_empty() {
+                if let Err(err) = tx.send(Ok(lines)) {
+                    warn!("Error sending input streaming result: {err}");
+                    return;
+                }
+            }
+            if at_end {
                 return;
             }
             if let Some(read_err) = maybe_err {
@@ -49,3 +143,39 @@ pub fn stream_input(num_lines: usize) -> (Receiver<Result<Vec<String>, Error>>,
     });
     (rx, thread_handle)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{stream_input, Lines};
+
+    #[test]
+    fn stream_all_lines_in_batches() {
+        let input = (0..10).map(|i| format!("line {i}\n")).collect::<String>();
+        let (rx, handle) = stream_input(std::io::Cursor::new(input), 4);
+        handle.join().unwrap();
+        let batches: Vec<Lines> = rx.iter().map(Result::unwrap).collect();
+        assert_eq!(
+            batches.iter().map(Lines::len).collect::<Vec<_>>(),
+            vec![4, 4, 2]
+        );
+        assert_eq!(batches[2].text[1], "line 9");
+    }
+
+    #[test]
+    fn stream_strips_escapes() {
+        let input = "\x1b[33mcommit b8e882d\x1b[m\n+added\n";
+        let (rx, handle) = stream_input(std::io::Cursor::new(input), 4);
+        handle.join().unwrap();
+        let lines = rx.recv().unwrap().unwrap();
+        assert_eq!(lines.text, vec!["commit b8e882d", "+added"]);
+        assert_eq!(lines.styles[0].len(), 1);
+        assert!(lines.styles[1].is_empty());
+    }
+
+    #[test]
+    fn stream_empty_input() {
+        let (rx, handle) = stream_input(std::io::empty(), 4);
+        handle.join().unwrap();
+        assert!(rx.recv().is_err());
+    }
+}
@@ -1,7 +1,20 @@
+pub mod ansi;
 pub mod app;
+pub mod commit;
+pub mod commit_filter;
+pub mod config;
 pub mod context_finder;
 pub mod error;
+pub mod fold;
+pub mod gutter;
+pub mod highlight;
+pub mod history;
 pub mod input;
+pub mod picker;
 pub mod search;
+pub mod side_by_side;
+pub mod syntax;
 pub mod ui;
 pub mod utils;
+pub mod view;
+pub mod word_diff;
@@ -1,5 +1,7 @@
 use cag::app::run_app;
+use cag::config::Config;
 use cag::error::Error;
+use cag::input::open_input;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -7,20 +9,47 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::path::Path;
 use tracing::{error, trace, Level};
 
 const ENVIRONMENT_VARIABLE_ENABLE_TRACING: &str = "ENABLE_TRACING";
 
+fn init_tracing(log_file: Option<&Path>) {
+    let file_appender = if let Some(log_file) = log_file {
+        let directory = log_file.parent().unwrap_or_else(|| Path::new("."));
+        let Some(file_name) = log_file.file_name() else {
+            eprintln!("Invalid log file {}", log_file.display());
+            return;
+        };
+        tracing_appender::rolling::never(directory, file_name)
+    } else {
+        match std::env::var(ENVIRONMENT_VARIABLE_ENABLE_TRACING) {
+            Ok(enable_tracing)
+                if enable_tracing == "1" || &enable_tracing.to_lowercase() == "true" =>
+            {
+                tracing_appender::rolling::hourly("./.logs/", "runlog")
+            }
+            _ => return,
+        }
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(Level::TRACE)
+        .with_writer(file_appender)
+        .init();
+}
+
 fn main() -> Result<(), Error> {
-    if let Ok(enable_tracing) = std::env::var(ENVIRONMENT_VARIABLE_ENABLE_TRACING) {
-        if enable_tracing == "1" || &enable_tracing.to_lowercase() == "true" {
-            let file_appender = tracing_appender::rolling::hourly("./.logs/", "runlog");
-            tracing_subscriber::fmt()
-                .with_max_level(Level::TRACE)
-                .with_writer(file_appender)
-                .init();
+    let config = Config::from_args();
+    init_tracing(config.log_file.as_deref());
+    let input = match open_input(&config.files) {
+        Ok(input) => input,
+        Err(err) => {
+            error!("{:?}", err);
+            eprintln!("{err}");
+            std::process::exit(1);
         }
-    }
+    };
+
     trace!("Enabling raw mode");
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -28,7 +57,7 @@ fn main() -> Result<(), Error> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let res = run_app(&mut terminal);
+    let res = run_app(&mut terminal, input, &config);
 
     trace!("Disabling raw mode");
 
@@ -0,0 +1,188 @@
+use tui_input::Input;
+
+/// Score added for each query character matching right after the previous
+/// one.
+const CONSECUTIVE_BONUS: usize = 4;
+/// Score added for each query character matching at the start of a word.
+const WORD_START_BONUS: usize = 2;
+
+/// An entry of a picker, jumping to `line` when chosen.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PickerItem {
+    pub text: String,
+    /// Line number of the input to jump to.
+    pub line: usize,
+}
+
+/// A popup listing places to jump to, narrowed down by fuzzy matching a
+/// query against the items.
+pub struct Picker {
+    pub title: String,
+    pub query: Input,
+    items: Vec<PickerItem>,
+    /// Indices of the items matching the query, the best match first.
+    matches: Vec<usize>,
+    /// Index in `matches` of the selected item.
+    selected: usize,
+}
+
+impl Picker {
+    #[must_use]
+    pub fn new(title: &str, items: Vec<PickerItem>) -> Self {
+        Picker {
+            title: title.to_string(),
+            query: Input::default(),
+            matches: (0..items.len()).collect(),
+            items,
+            selected: 0,
+        }
+    }
+
+    /// Selects the last item at or before `line_num`, such as the commit the
+    /// viewed line belongs to.
+    pub fn select_line(&mut self, line_num: usize) {
+        self.selected = self
+            .matches
+            .iter()
+            .rposition(|index| self.items[*index].line <= line_num)
+            .unwrap_or_default();
+    }
+
+    /// Narrows down the items to the ones matching the query, selecting the
+    /// best match.
+    pub fn update_matches(&mut self) {
+        let query = self.query.value();
+        let mut scored: Vec<(usize, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| Some((fuzzy_score(query, &item.text)?, index)))
+            .collect();
+        scored.sort_by(|(score, index), (other_score, other_index)| {
+            other_score.cmp(score).then(index.cmp(other_index))
+        });
+        self.matches = scored.into_iter().map(|(_score, index)| index).collect();
+        self.selected = 0;
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Index of the selected item among the matching items.
+    #[must_use]
+    pub fn selected(&self) -> Option<usize> {
+        (self.selected < self.matches.len()).then_some(self.selected)
+    }
+
+    #[must_use]
+    pub fn selected_item(&self) -> Option<&PickerItem> {
+        self.matches
+            .get(self.selected)
+            .and_then(|index| self.items.get(*index))
+    }
+
+    /// The items matching the query, the best match first.
+    pub fn matches(&self) -> impl Iterator<Item = &PickerItem> {
+        self.matches
+            .iter()
+            .filter_map(|index| self.items.get(*index))
+    }
+
+    /// Number of the items matching the query.
+    #[must_use]
+    pub fn num_matches(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Number of all items.
+    #[must_use]
+    pub fn num_items(&self) -> usize {
+        self.items.len()
+    }
+}
+
+/// Scores how well `text` matches `query` when the characters of the query
+/// appear in it in order, ignoring case. Consecutive characters and
+/// characters at the start of words score higher.
+///
+/// # Returns
+/// * `Option<usize>` - The score, or None if `text` does not match.
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let Some(query_char) = query.peek() else {
+            break;
+        };
+        let matched = c == *query_char;
+        if matched {
+            query.next();
+            score += 1;
+            if previous_matched {
+                score += CONSECUTIVE_BONUS;
+            }
+            if previous.is_none_or(|previous| !previous.is_alphanumeric()) {
+                score += WORD_START_BONUS;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    query.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fuzzy_score, Picker, PickerItem};
+
+    fn picker() -> Picker {
+        let items = [
+            "b8e882d Read stdin",
+            "1a2b3c4 Parse commits",
+            "9f8e7d6 Fix reader",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| PickerItem {
+            text: text.to_string(),
+            line: i * 100,
+        })
+        .collect();
+        Picker::new("Commits", items)
+    }
+
+    #[test]
+    fn fuzzy_scores() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("rdr", "Fix reader").is_some());
+        assert!(fuzzy_score("rdx", "Fix reader").is_none());
+        assert!(fuzzy_score("rs", "Read stdin") > fuzzy_score("rs", "errors"));
+    }
+
+    #[test]
+    fn filter_and_select() {
+        let mut picker = picker();
+        picker.select_line(150);
+        assert_eq!(picker.selected_item().unwrap().line, 100);
+        picker.query = "read".into();
+        picker.update_matches();
+        assert_eq!(picker.num_matches(), 2);
+        assert_eq!(picker.selected_item().unwrap().line, 0);
+        picker.select_next();
+        picker.select_next();
+        assert_eq!(picker.selected_item().unwrap().line, 200);
+        picker.query = "zzz".into();
+        picker.update_matches();
+        assert!(picker.selected().is_none());
+        assert!(picker.selected_item().is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::Error;
 use aho_corasick::AhoCorasick;
+use regex::{Regex, RegexBuilder};
 use tui_input::Input;
 
 #[derive(Debug, Eq, PartialEq)]
@@ -13,51 +14,500 @@ pub enum SearchState {
     Searching { term: Input, position: usize },
 }
 
-/// Searches for a term in the provided lines of text starting from a given position.
+/// How letter case is taken into account when matching a search term.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum CaseMode {
+    /// Match letters regardless of their case
+    #[default]
+    Insensitive,
+    /// Match letters only in the same case
+    Sensitive,
+    /// Case sensitive only if the term contains an uppercase letter
+    Smart,
+}
+
+impl CaseMode {
+    /// The mode toggled to from this one at runtime.
+    #[must_use]
+    pub fn next(self) -> Self {
+        match self {
+            CaseMode::Insensitive => CaseMode::Sensitive,
+            CaseMode::Sensitive => CaseMode::Smart,
+            CaseMode::Smart => CaseMode::Insensitive,
+        }
+    }
+}
+
+/// Options controlling how a search term is matched.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchOptions {
+    /// Interpret the search term as a regular expression instead of a literal
+    /// string.
+    pub regex: bool,
+    pub case: CaseMode,
+}
+
+impl SearchOptions {
+    /// Whether `term` is matched case sensitively with these options.
+    #[must_use]
+    pub fn is_case_sensitive(&self, term: &str) -> bool {
+        match self.case {
+            CaseMode::Insensitive => false,
+            CaseMode::Sensitive => true,
+            CaseMode::Smart => has_uppercase(term, self.regex),
+        }
+    }
+}
+
+/// Checks whether `term` has an uppercase letter, ignoring escape sequences
+/// such as `\W` of a regular expression.
+fn has_uppercase(term: &str, regex: bool) -> bool {
+    let mut chars = term.chars();
+    while let Some(c) = chars.next() {
+        if regex && c == '\\' {
+            chars.next();
+        } else if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+/// A compiled search term used both for searching and for highlighting the
+/// matches.
+pub enum Matcher {
+    Literal(AhoCorasick),
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// Compiles `term` according to `options`.
+    ///
+    /// Case insensitive literal terms are matched with an escaped regular
+    /// expression, as the Aho-Corasick automaton only folds ASCII letters.
+    ///
+    /// # Errors
+    /// This function can return errors in the following cases:
+    /// * If there is an error building the Aho-Corasick automaton
+    /// * If the term is not a valid regular expression in regex mode
+    pub fn new(term: &str, options: SearchOptions) -> Result<Self, Error> {
+        let case_insensitive = !options.is_case_sensitive(term);
+        if options.regex {
+            let regex = RegexBuilder::new(term)
+                .case_insensitive(case_insensitive)
+                .build()?;
+            Ok(Matcher::Regex(regex))
+        } else if case_insensitive {
+            let regex = RegexBuilder::new(&regex::escape(term))
+                .case_insensitive(true)
+                .build()?;
+            Ok(Matcher::Regex(regex))
+        } else {
+            Ok(Matcher::Literal(AhoCorasick::new([term])?))
+        }
+    }
+
+    /// Checks whether `line` contains a match.
+    #[must_use]
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            Matcher::Literal(ac) => ac.is_match(line),
+            Matcher::Regex(regex) => regex.is_match(line),
+        }
+    }
+
+    /// Finds the byte ranges of the non-empty matches in `line`.
+    #[must_use]
+    pub fn find_ranges(&self, line: &str) -> Vec<(usize, usize)> {
+        match self {
+            Matcher::Literal(ac) => ac
+                .find_iter(line)
+                .map(|m| (m.start(), m.end()))
+                .filter(|(start, end)| start < end)
+                .collect(),
+            Matcher::Regex(regex) => regex
+                .find_iter(line)
+                .map(|m| (m.start(), m.end()))
+                .filter(|(start, end)| start < end)
+                .collect(),
+        }
+    }
+}
+
+/// Rows showing lines that match a search term, kept up to date as more input
+/// is read.
+#[derive(Debug, Default)]
+pub struct Matches {
+    term: String,
+    options: SearchOptions,
+    /// The matching rows in ascending order.
+    rows: Vec<usize>,
+    /// Number of rows searched for matches so far.
+    searched: usize,
+}
+
+/// Where the viewed row is among the matches of a search.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MatchCount {
+    /// 1-based number of the match at the viewed row, if it is a match.
+    pub current: Option<usize>,
+    /// Number of matching rows read so far.
+    pub total: usize,
+}
+
+impl Matches {
+    /// Searches the rows not searched yet for `term`, starting over if the
+    /// term or the options have changed since the last update.
+    ///
+    /// # Arguments
+    /// * `rows` - Line numbers of the lines in `all_lines` shown on each row.
+    pub fn update(
+        &mut self,
+        term: &str,
+        options: SearchOptions,
+        matcher: &Matcher,
+        all_lines: &[String],
+        rows: &[usize],
+    ) {
+        if self.term != term || self.options != options {
+            *self = Matches {
+                term: term.to_string(),
+                options,
+                ..Matches::default()
+            };
+        }
+        self.rows.extend(
+            rows.iter()
+                .enumerate()
+                .skip(self.searched)
+                .filter(|(_row, line_num)| is_match_at(matcher, all_lines, **line_num))
+                .map(|(row, _line_num)| row),
+        );
+        self.searched = self.searched.max(rows.len());
+    }
+
+    /// Whether no rows searched so far match.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Counts the matches, locating the row at `position` among them.
+    #[must_use]
+    pub fn count(&self, position: usize) -> MatchCount {
+        MatchCount {
+            current: self
+                .rows
+                .binary_search(&position)
+                .ok()
+                .map(|index| index + 1),
+            total: self.rows.len(),
+        }
+    }
+}
+
+fn is_match_at(matcher: &Matcher, all_lines: &[String], line_num: usize) -> bool {
+    all_lines
+        .get(line_num)
+        .is_some_and(|line| matcher.is_match(line))
+}
+
+/// Searches for a match in the provided lines of text starting from a given
+/// row.
 ///
-/// # Returns
-/// * `Result<Option<usize>, Error>` - Returns an Ok result containing an
-///   Option with the line number of the first match if found, otherwise None.
-///   Returns an Error if the search fails.
+/// # Arguments
+/// * `rows` - Line numbers of the lines in `all_lines` shown on each row.
 ///
-/// # Errors
-/// This function can return errors in the following cases:
-/// * If there is an error building the Aho-Corasick automaton
+/// # Returns
+/// * `Option<usize>` - The row of the first match if found, otherwise None.
+#[must_use]
 pub fn search(
-    term: &Input,
+    matcher: &Matcher,
     position: usize,
     all_lines: &[String],
+    rows: &[usize],
     direction: &SearchDirection,
-) -> Result<Option<usize>, Error> {
-    let ac = AhoCorasick::builder()
-        .ascii_case_insensitive(true)
-        .build([term.value()])?;
-    let match_lines: Vec<usize> = match direction {
-        SearchDirection::Backwards => all_lines
+) -> Option<usize> {
+    let is_match = |line_num: &usize| is_match_at(matcher, all_lines, *line_num);
+    match direction {
+        SearchDirection::Backwards => rows
+            .get(..position.min(rows.len()))?
             .iter()
-            .enumerate()
-            .rev()
-            .skip(all_lines.len() - position)
-            .filter_map(|(line_num, line)| {
-                if ac.find_iter(line).next().is_some() {
-                    Some(line_num)
-                } else {
-                    None
-                }
-            })
-            .collect(),
-        SearchDirection::Forward => all_lines
+            .rposition(is_match),
+        SearchDirection::Forward => rows
+            .get(position..)?
             .iter()
-            .enumerate()
-            .skip(position)
-            .filter_map(|(line_num, line)| {
-                if ac.find_iter(line).next().is_some() {
-                    Some(line_num)
-                } else {
-                    None
-                }
-            })
-            .collect(),
+            .position(is_match)
+            .map(|offset| position + offset),
+    }
+}
+
+/// Searches like `search`, continuing from the other end of the rows if
+/// `wrap` is set and there is no match before reaching the end.
+///
+/// # Returns
+/// * `Option<(usize, bool)>` - The row of the match and whether the search
+///   wrapped around to find it, or None if there is no match.
+#[must_use]
+pub fn search_wrapping(
+    matcher: &Matcher,
+    position: usize,
+    all_lines: &[String],
+    rows: &[usize],
+    direction: &SearchDirection,
+    wrap: bool,
+) -> Option<(usize, bool)> {
+    if let Some(row) = search(matcher, position, all_lines, rows, direction) {
+        return Some((row, false));
+    }
+    if !wrap {
+        return None;
+    }
+    let restart = match direction {
+        SearchDirection::Backwards => rows.len(),
+        SearchDirection::Forward => 0,
+    };
+    search(matcher, restart, all_lines, rows, direction).map(|row| (row, true))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        search, search_wrapping, CaseMode, MatchCount, Matcher, Matches, SearchDirection,
+        SearchOptions,
     };
-    Ok(match_lines.first().copied())
+
+    fn lines() -> Vec<String> {
+        ["fn main() {", "fn parse_test() {", "+    x.unwrap()", "}"]
+            .into_iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    fn rows(lines: &[String]) -> Vec<usize> {
+        (0..lines.len()).collect()
+    }
+
+    #[test]
+    fn literal_search() {
+        let matcher = Matcher::new("FN", SearchOptions::default()).unwrap();
+        let lines = lines();
+        assert_eq!(
+            search(
+                &matcher,
+                1,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Forward
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            search(
+                &matcher,
+                1,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Backwards
+            ),
+            Some(0)
+        );
+        assert_eq!(matcher.find_ranges("fn a fn"), vec![(0, 2), (5, 7)]);
+    }
+
+    #[test]
+    fn search_shown_rows() {
+        let matcher = Matcher::new("fn", SearchOptions::default()).unwrap();
+        let lines = lines();
+        let rows = [1, 2, 3];
+        assert_eq!(
+            search(&matcher, 1, &lines, &rows, &SearchDirection::Backwards),
+            Some(0)
+        );
+        assert_eq!(
+            search(&matcher, 1, &lines, &rows, &SearchDirection::Forward),
+            None
+        );
+    }
+
+    #[test]
+    fn regex_search() {
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let lines = lines();
+        let matcher = Matcher::new(r"fn \w+_test", options).unwrap();
+        assert_eq!(
+            search(
+                &matcher,
+                0,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Forward
+            ),
+            Some(1)
+        );
+        let matcher = Matcher::new(r"^\+.*unwrap\(", options).unwrap();
+        assert_eq!(
+            search(
+                &matcher,
+                0,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Forward
+            ),
+            Some(2)
+        );
+        assert_eq!(matcher.find_ranges(&lines[2]), vec![(0, 14)]);
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        assert!(Matcher::new("unwrap(", options).is_err());
+        assert!(Matcher::new("unwrap(", SearchOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn empty_regex_has_no_ranges() {
+        let options = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let matcher = Matcher::new("", options).unwrap();
+        assert!(matcher.find_ranges("text").is_empty());
+    }
+
+    #[test]
+    fn case_modes() {
+        let options = |case| SearchOptions {
+            case,
+            ..SearchOptions::default()
+        };
+        let insensitive = Matcher::new("error", options(CaseMode::Insensitive)).unwrap();
+        assert!(insensitive.is_match("Error"));
+        let sensitive = Matcher::new("error", options(CaseMode::Sensitive)).unwrap();
+        assert!(!sensitive.is_match("Error"));
+        assert!(sensitive.is_match("error"));
+        let smart_lower = Matcher::new("error", options(CaseMode::Smart)).unwrap();
+        assert!(smart_lower.is_match("ERROR"));
+        let smart_upper = Matcher::new("Error", options(CaseMode::Smart)).unwrap();
+        assert!(!smart_upper.is_match("ERROR"));
+        assert!(smart_upper.is_match("Error"));
+    }
+
+    #[test]
+    fn smart_case_ignores_regex_escapes() {
+        let options = SearchOptions {
+            regex: true,
+            case: CaseMode::Smart,
+        };
+        let matcher = Matcher::new(r"fn\W", options).unwrap();
+        assert!(matcher.is_match("FN("));
+        let matcher = Matcher::new(r"Fn\W", options).unwrap();
+        assert!(!matcher.is_match("FN("));
+    }
+
+    #[test]
+    fn non_ascii_case_folding() {
+        let matcher = Matcher::new("äiti", SearchOptions::default()).unwrap();
+        assert_eq!(matcher.find_ranges("ÄITI ja äiti"), vec![(0, 5), (9, 14)]);
+        let options = SearchOptions {
+            case: CaseMode::Smart,
+            ..SearchOptions::default()
+        };
+        let matcher = Matcher::new("Äiti", options).unwrap();
+        assert!(!matcher.is_match("äiti"));
+    }
+
+    #[test]
+    fn count_matches() {
+        let options = SearchOptions::default();
+        let matcher = Matcher::new("fn", options).unwrap();
+        let mut lines = lines();
+        let mut matches = Matches::default();
+        matches.update("fn", options, &matcher, &lines, &[0]);
+        assert_eq!(
+            matches.count(0),
+            MatchCount {
+                current: Some(1),
+                total: 1
+            }
+        );
+        matches.update("fn", options, &matcher, &lines, &rows(&lines));
+        assert_eq!(
+            matches.count(1),
+            MatchCount {
+                current: Some(2),
+                total: 2
+            }
+        );
+        assert_eq!(matches.count(2).current, None);
+        lines.push("fn more() {}".to_string());
+        matches.update("fn", options, &matcher, &lines, &rows(&lines));
+        assert_eq!(matches.count(4).total, 3);
+        let matcher = Matcher::new("unwrap", options).unwrap();
+        matches.update("unwrap", options, &matcher, &lines, &rows(&lines));
+        assert_eq!(
+            matches.count(2),
+            MatchCount {
+                current: Some(1),
+                total: 1
+            }
+        );
+    }
+
+    #[test]
+    fn wrap_around() {
+        let matcher = Matcher::new("fn", SearchOptions::default()).unwrap();
+        let lines = lines();
+        assert_eq!(
+            search_wrapping(
+                &matcher,
+                2,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Forward,
+                true
+            ),
+            Some((0, true))
+        );
+        assert_eq!(
+            search_wrapping(
+                &matcher,
+                1,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Forward,
+                true
+            ),
+            Some((1, false))
+        );
+        assert_eq!(
+            search_wrapping(
+                &matcher,
+                0,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Backwards,
+                true
+            ),
+            Some((1, true))
+        );
+        assert_eq!(
+            search_wrapping(
+                &matcher,
+                2,
+                &lines,
+                &rows(&lines),
+                &SearchDirection::Forward,
+                false
+            ),
+            None
+        );
+    }
 }
@@ -0,0 +1,163 @@
+use crate::context_finder::{ContextFinder, LineKind};
+use itertools::{EitherOrBoth, Itertools};
+
+/// A row of the side-by-side view, referring to the rows of the unified view
+/// by their index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SplitRow {
+    /// A row spanning both columns, such as a commit or hunk header.
+    Full(usize),
+    /// A line of the old version on the left paired with a line of the new
+    /// version on the right. Context lines are shown on both sides.
+    Pair(Option<usize>, Option<usize>),
+}
+
+/// Lays out the rows of the unified view in two columns.
+///
+/// Inside hunks each run of removed lines is paired line by line with the run
+/// of added lines following it, and context lines are shown on both sides.
+/// All other lines span both columns.
+///
+/// # Arguments
+/// * `lines` - All lines of the input.
+/// * `rows` - Line numbers of the rows to lay out.
+/// * `cf` - The `ContextFinder` used to classify the lines.
+/// * `in_hunk` - Whether the first row is inside a hunk.
+#[must_use]
+pub fn split_rows(
+    lines: &[String],
+    rows: &[usize],
+    cf: &ContextFinder,
+    mut in_hunk: bool,
+) -> Vec<SplitRow> {
+    let mut split = Vec::with_capacity(rows.len());
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for (index, line_num) in rows.iter().enumerate() {
+        let Some(line) = lines.get(*line_num) else {
+            continue;
+        };
+        match cf.line_kind(line) {
+            LineKind::Removed if in_hunk => {
+                if !added.is_empty() {
+                    pair_runs(&mut split, &mut removed, &mut added);
+                }
+                removed.push(index);
+            }
+            LineKind::Added if in_hunk => added.push(index),
+            kind => {
+                pair_runs(&mut split, &mut removed, &mut added);
+                match kind {
+                    LineKind::Hunk => in_hunk = true,
+                    LineKind::Commit | LineKind::FileHeader => in_hunk = false,
+                    LineKind::Added | LineKind::Removed | LineKind::Other => (),
+                }
+                if kind == LineKind::Other && in_hunk && (line.is_empty() || line.starts_with(' '))
+                {
+                    split.push(SplitRow::Pair(Some(index), Some(index)));
+                } else {
+                    split.push(SplitRow::Full(index));
+                }
+            }
+        }
+    }
+    pair_runs(&mut split, &mut removed, &mut added);
+    split
+}
+
+/// Counts the rows of the unified view shown by the first `count` rows of
+/// `split`, up to the first row not shown.
+///
+/// # Returns
+/// * `usize` - The number of rows to move a screen forward without skipping
+///   any row.
+#[must_use]
+pub fn unified_rows_shown(split: &[SplitRow], count: usize) -> usize {
+    let mut shown: Vec<usize> = split
+        .iter()
+        .take(count)
+        .flat_map(|split_row| match split_row {
+            SplitRow::Full(index) => [Some(*index), None],
+            SplitRow::Pair(old, new) => [*old, *new],
+        })
+        .flatten()
+        .collect();
+    shown.sort_unstable();
+    shown.dedup();
+    shown
+        .iter()
+        .enumerate()
+        .take_while(|(position, index)| position == *index)
+        .count()
+}
+
+/// Pairs the runs of removed and added lines read so far line by line.
+fn pair_runs(split: &mut Vec<SplitRow>, removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+    split.extend(
+        removed
+            .drain(..)
+            .zip_longest(added.drain(..))
+            .map(|pair| match pair {
+                EitherOrBoth::Both(old, new) => SplitRow::Pair(Some(old), Some(new)),
+                EitherOrBoth::Left(old) => SplitRow::Pair(Some(old), None),
+                EitherOrBoth::Right(new) => SplitRow::Pair(None, Some(new)),
+            }),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_rows, unified_rows_shown, SplitRow};
+    use crate::context_finder::{ContextFinder, InputType};
+
+    #[test]
+    fn pair_removed_and_added_runs() {
+        let lines: Vec<String> = [
+            "diff --git a/src/main.rs b/src/main.rs",
+            "@@ -1,5 +1,4 @@",
+            " fn main() {",
+            "-    let a = 1;",
+            "-    let b = 2;",
+            "+    let a = 3;",
+            "     run();",
+            "+    done();",
+            "-}",
+            "\\ No newline at end of file",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect();
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let rows: Vec<usize> = (0..lines.len()).collect();
+        assert_eq!(
+            split_rows(&lines, &rows, &cf, false),
+            vec![
+                SplitRow::Full(0),
+                SplitRow::Full(1),
+                SplitRow::Pair(Some(2), Some(2)),
+                SplitRow::Pair(Some(3), Some(5)),
+                SplitRow::Pair(Some(4), None),
+                SplitRow::Pair(Some(6), Some(6)),
+                SplitRow::Pair(None, Some(7)),
+                SplitRow::Pair(Some(8), None),
+                SplitRow::Full(9),
+            ]
+        );
+        let split = split_rows(&lines, &rows, &cf, false);
+        assert_eq!(unified_rows_shown(&split, 3), 3);
+        assert_eq!(unified_rows_shown(&split, 4), 4);
+        assert_eq!(unified_rows_shown(&split, 5), 6);
+        assert_eq!(unified_rows_shown(&split, 20), 10);
+        assert_eq!(
+            split_rows(&lines, &rows[6..8], &cf, true),
+            vec![
+                SplitRow::Pair(Some(0), Some(0)),
+                SplitRow::Pair(None, Some(1))
+            ]
+        );
+        assert_eq!(
+            split_rows(&lines, &rows[6..8], &cf, false),
+            vec![SplitRow::Full(0), SplitRow::Full(1)]
+        );
+    }
+}
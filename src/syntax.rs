@@ -0,0 +1,208 @@
+use crate::ansi::AnsiStyles;
+use crate::context_finder::{ContextFinder, LineKind};
+use ratatui::style::Color;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use tracing::trace;
+
+/// Theme of the highlighted code, one of the themes bundled with `syntect`.
+const THEME: &str = "base16-ocean.dark";
+/// Maximum number of lines highlighted above a line to find the state of the
+/// parser at it. Lines further down in a hunk are highlighted from scratch.
+const MAX_LOOKBACK: usize = 1000;
+/// Number of lines highlighted at once below a line not highlighted yet.
+const LOOKAHEAD: usize = 200;
+
+/// Colors of the code of a line by the byte ranges they apply to.
+pub type Colors = Vec<(usize, usize, Color)>;
+
+/// Highlights the code in hunks in the language of the changed file.
+///
+/// The language is inferred from the path of the file. Lines are highlighted
+/// only when they are shown, starting from their hunk so that the parser
+/// knows the code around them, and the results are kept for showing the lines
+/// again. The syntaxes and the theme are loaded when the first hunk of a file
+/// is highlighted.
+#[derive(Default)]
+pub struct SyntaxHighlighter {
+    syntaxes: OnceCell<(SyntaxSet, Theme)>,
+    /// Colors of the lines highlighted so far by their line number.
+    highlighted: HashMap<usize, Colors>,
+}
+
+impl SyntaxHighlighter {
+    /// Highlights the lines shown on the screen.
+    ///
+    /// # Arguments
+    /// * `lines` - All lines of the input.
+    /// * `styles` - Styles set by ANSI escape sequences of `lines`.
+    /// * `rows` - Line numbers of the lines shown.
+    /// * `cf` - The `ContextFinder` used to find the hunks and files.
+    ///
+    /// # Returns
+    /// * `Vec<Colors>` - The colors of each line shown, empty for lines
+    ///   outside hunks, in files of unknown languages or colored by escape
+    ///   sequences.
+    pub fn highlight(
+        &mut self,
+        lines: &[String],
+        styles: &[AnsiStyles],
+        rows: &[usize],
+        cf: &ContextFinder,
+    ) -> Vec<Colors> {
+        rows.iter()
+            .map(|line_num| {
+                if styles
+                    .get(*line_num)
+                    .is_some_and(|styles| !styles.is_empty())
+                {
+                    return Colors::new();
+                }
+                if !self.highlighted.contains_key(line_num) {
+                    self.highlight_hunk(lines, *line_num, cf);
+                }
+                self.highlighted.get(line_num).cloned().unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Highlights the hunk of line `line_num` from its start, or from
+    /// `MAX_LOOKBACK` lines above the line, to `LOOKAHEAD` lines below it.
+    ///
+    /// The removed and context lines are parsed as the old version of the code
+    /// and the added and context lines as the new version.
+    fn highlight_hunk(&mut self, lines: &[String], line_num: usize, cf: &ContextFinder) {
+        self.highlighted.insert(line_num, Colors::new());
+        let (Some(file_start), Some(hunk_start)) = cf.section_starts(lines, line_num) else {
+            return;
+        };
+        let Some(path) = lines.get(file_start).and_then(|line| cf.file_path(line)) else {
+            return;
+        };
+        let (syntaxes, theme) = self.syntaxes.get_or_init(|| {
+            trace!("Loading syntaxes");
+            let mut themes = ThemeSet::load_defaults().themes;
+            (
+                SyntaxSet::load_defaults_nonewlines(),
+                themes.remove(THEME).unwrap_or_default(),
+            )
+        });
+        let Some(syntax) = find_syntax(syntaxes, path) else {
+            return;
+        };
+        trace!("Highlighting hunk at {hunk_start} as {}", syntax.name);
+        let mut old = HighlightLines::new(syntax, theme);
+        let mut new = HighlightLines::new(syntax, theme);
+        let start = (hunk_start + 1).max(line_num.saturating_sub(MAX_LOOKBACK));
+        let end = (line_num + LOOKAHEAD).min(lines.len());
+        for (offset, line) in lines[start..end].iter().enumerate() {
+            let (kind, code) = match (cf.line_kind(line), line.get(1..)) {
+                (LineKind::Removed, Some(code)) => (LineKind::Removed, code),
+                (LineKind::Added, Some(code)) => (LineKind::Added, code),
+                (LineKind::Other, code) if line.is_empty() || line.starts_with(' ') => {
+                    (LineKind::Other, code.unwrap_or_default())
+                }
+                (LineKind::Other, _) if line.starts_with('\\') => continue,
+                _ => break,
+            };
+            let regions = match kind {
+                LineKind::Removed => old.highlight_line(code, syntaxes),
+                LineKind::Added => new.highlight_line(code, syntaxes),
+                _ => {
+                    // Keep the old version parsed up to the same line.
+                    let _ = old.highlight_line(code, syntaxes);
+                    new.highlight_line(code, syntaxes)
+                }
+            };
+            let Ok(regions) = regions else {
+                break;
+            };
+            let mut colors = Colors::with_capacity(regions.len());
+            // Skip the `-`, `+` or ` ` marker.
+            let mut region_start = usize::from(!line.is_empty());
+            for (style, text) in regions {
+                let region_end = region_start + text.len();
+                let color = style.foreground;
+                colors.push((
+                    region_start,
+                    region_end,
+                    Color::Rgb(color.r, color.g, color.b),
+                ));
+                region_start = region_end;
+            }
+            self.highlighted.insert(start + offset, colors);
+        }
+    }
+}
+
+/// The syntax of the language of the file at `path`, found by its extension
+/// or, for files such as `Makefile`, by its name.
+fn find_syntax<'a>(syntaxes: &'a SyntaxSet, path: &str) -> Option<&'a SyntaxReference> {
+    let path = Path::new(path);
+    path.extension()
+        .and_then(|extension| syntaxes.find_syntax_by_extension(extension.to_str()?))
+        .or_else(|| {
+            let name = path.file_name()?.to_str()?;
+            syntaxes.find_syntax_by_extension(name)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::SyntaxHighlighter;
+    use crate::ansi::AnsiStyles;
+    use crate::context_finder::{ContextFinder, InputType};
+    use ratatui::style::{Color, Style};
+
+    fn hunk(path: &str) -> Vec<String> {
+        [
+            format!("diff --git a/{path} b/{path}"),
+            format!("--- a/{path}"),
+            format!("+++ b/{path}"),
+            "@@ -1,2 +1,2 @@".to_string(),
+            " fn main() {".to_string(),
+            "-    let a = \"old\";".to_string(),
+            "+    let a = \"new\";".to_string(),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn highlight_by_path() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let mut highlighter = SyntaxHighlighter::default();
+        let lines = hunk("src/main.rs");
+        let colors = highlighter.highlight(&lines, &[], &[3, 6, 5], &cf);
+        assert!(colors[0].is_empty());
+        for (line, colors) in [&lines[6], &lines[5]].into_iter().zip(&colors[1..]) {
+            assert_eq!(colors.first().map(|(start, _, _)| *start), Some(1));
+            assert_eq!(colors.last().map(|(_, end, _)| *end), Some(line.len()));
+        }
+        let mut distinct: Vec<_> = colors[1].iter().map(|(_, _, color)| *color).collect();
+        distinct.dedup();
+        assert!(distinct.len() > 1);
+        let unknown = hunk("notes.unknown-extension");
+        let mut highlighter = SyntaxHighlighter::default();
+        assert!(highlighter.highlight(&unknown, &[], &[6], &cf)[0].is_empty());
+    }
+
+    #[test]
+    fn lazy_and_skipping_colored_lines() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let mut highlighter = SyntaxHighlighter::default();
+        let lines = hunk("src/main.rs");
+        assert!(highlighter.highlight(&lines, &[], &[0, 1], &cf)[1].is_empty());
+        assert!(highlighter.syntaxes.get().is_none());
+        let mut styles = vec![AnsiStyles::new(); lines.len()];
+        styles[6].push((0..1, Style::new().fg(Color::Green)));
+        assert!(highlighter.highlight(&lines, &styles, &[6], &cf)[0].is_empty());
+        assert!(highlighter.syntaxes.get().is_none());
+        assert!(!highlighter.highlight(&lines, &styles, &[5], &cf)[0].is_empty());
+        assert!(highlighter.syntaxes.get().is_some());
+    }
+}
@@ -1,64 +1,137 @@
-use crate::search::SearchState;
+use crate::ansi::AnsiStyles;
+use crate::context_finder::{Boundary, Context, ContextFinder, InputType, LineKind};
+use crate::fold::Fold;
+use crate::gutter::Gutter;
+use crate::highlight::{fit, overlay, skip_columns, wrap_columns};
+use crate::picker::Picker;
+use crate::search::{CaseMode, MatchCount, Matcher, SearchOptions, SearchState};
+use crate::side_by_side::SplitRow;
+use crate::syntax::Colors;
+use crate::word_diff::Ranges;
 use crate::{app::State, error::Error};
-use aho_corasick::AhoCorasick;
-use ratatui::style::Style;
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    layout::{Constraint, Direction, Flex, Layout, Rect},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 use tracing::{debug, trace};
 use tui_input::Input;
 
+/// Maximum number of lines shown in the context pane.
+const MAX_CONTEXT_LINES: usize = 8;
+/// Share of the screen width and height taken by a picker popup, in percent.
+const PICKER_SIZE_PERCENT: u16 = 80;
+
+/// Content of one screen of the pager.
+pub struct Screen<'a> {
+    /// Lines of the input.
+    pub lines: &'a [String],
+    /// Styles set by ANSI escape sequences of `lines`.
+    pub styles: &'a [AnsiStyles],
+    /// Line numbers of the lines to display on each row of the screen.
+    pub rows: &'a [usize],
+    /// Summaries of the folded sections starting on each row of `rows`.
+    pub folds: &'a [Option<Fold>],
+    /// Byte ranges of the changed words of each row of `rows`.
+    pub changed: &'a [Ranges],
+    /// Colors of the code of each row of `rows`.
+    pub syntax: &'a [Colors],
+    /// Line numbers shown left of each row of `rows`, empty without a gutter.
+    pub gutters: &'a [Gutter],
+    /// Layout of `rows` in two columns in the side-by-side mode.
+    pub split: Option<&'a [SplitRow]>,
+    /// Contexts of the viewed position from the outermost to the innermost
+    /// level, such as the commit message, file and hunk header.
+    pub contexts: &'a [Context<'a>],
+    /// Search term to highlight in the lines.
+    pub hilights: Option<&'a Matcher>,
+    /// Options of the current search.
+    pub search_options: SearchOptions,
+    /// Whether the current search term is not a valid pattern.
+    pub search_error: bool,
+    /// Matches of a valid search term read so far.
+    pub matches: Option<MatchCount>,
+    /// Message shown until the next key press, such as a search wrapping
+    /// around.
+    pub message: Option<&'a str>,
+    /// Number of columns the lines are scrolled to the left.
+    pub horizontal_scroll: usize,
+    /// Whether long lines are wrapped instead of cut at the edge of the
+    /// screen. Lines are not wrapped in the side-by-side mode.
+    pub wrap: bool,
+    /// Position and state of the input shown in the status bar.
+    pub status: Status<'a>,
+}
+
+/// Content of the status bar.
+pub struct Status<'a> {
+    /// Zero based number of the line at the top of the screen.
+    pub line: Option<usize>,
+    /// Number of the lines read so far.
+    pub total_lines: usize,
+    /// Share of the shown rows down to the bottom of the screen, in percent.
+    pub percent: usize,
+    /// Short hash of the commit at the top of the screen.
+    pub commit: Option<&'a str>,
+    /// Type of the input the contexts are found by.
+    pub input_type: InputType,
+    /// Whether more input is still being read.
+    pub streaming: bool,
+}
+
 /// Renders the pager UI.
 ///
 /// This function is responsible for rendering the pager UI, including the
-/// commit message,
+/// nested contexts of the viewed position,
 /// git log, and search box if applicable. It handles different states such as
 /// `Search` and `Pager`.
 ///
 /// # Arguments
 /// * `f` - A mutable reference to the `Frame` to render the UI.
 /// * `state` - A reference to the current `State` of the application.
-/// * `git_log` - A slice of strings representing the git log to display.
-/// * `commit` - An optional slice of strings representing the commit message
-///   to display.
+/// * `screen` - The lines, contexts and search term to display.
+/// * `cf` - The `ContextFinder` used to classify lines for coloring them.
 /// * `vertical_size` - A mutable reference to a `u16` to store the vertical
 ///   size of the rendered UI.
-/// * `hilights` - An optional string containing the search term to highlight
-///   in the git log.
+/// * `horizontal_size` - A mutable reference to a `u16` to store the width of
+///   the rendered lines.
 ///
 /// # Errors
 /// This function can return errors in the following cases:
-/// * If there is an error building the Aho-Corasick automaton (`aho_corasick::Error`).
 /// * If there is an error rendering the widgets (`tui::Error`).
 pub fn pager(
     f: &mut Frame,
     state: &State,
-    git_log: &[String],
-    commit: Option<&[String]>,
+    screen: &Screen,
+    cf: &ContextFinder,
     vertical_size: &mut u16,
-    hilights: Option<String>,
+    horizontal_size: &mut u16,
 ) -> Result<(), Error> {
+    let contexts = screen.contexts;
     trace!("Rendering screen");
-    let hilight_style = Style::new()
-        .fg(ratatui::style::Color::Black)
-        .bg(ratatui::style::Color::Gray);
-    let commit_len = commit.map_or(0, |commit| commit.iter().len() + 1);
-    let commit = commit.map(|commit| commit.join("\n"));
+    let max_context_lines = MAX_CONTEXT_LINES
+        .min(usize::from(f.area().height) / 3)
+        .max(contexts.len());
+    let context = context_lines(contexts, cf, max_context_lines);
+    let context_len = if context.is_empty() {
+        0
+    } else {
+        context.len().min(max_context_lines) + 1
+    };
 
     let layout = match state {
-        State::Search { .. } => vec![
-            #[allow(clippy::cast_possible_truncation)]
-            Constraint::Max(std::cmp::min(7, commit_len as u16)),
+        State::Search { .. } | State::Filter { .. } | State::Command { .. } => vec![
+            Constraint::Max(u16::try_from(context_len)?),
             Constraint::Min(8),
             Constraint::Max(3),
+            Constraint::Length(1),
         ],
-        State::Pager => vec![
-            #[allow(clippy::cast_possible_truncation)]
-            Constraint::Max(std::cmp::min(7, commit_len as u16)),
+        State::Pager | State::Picker(_) => vec![
+            Constraint::Max(u16::try_from(context_len)?),
             Constraint::Min(8),
+            Constraint::Length(1),
         ],
         State::Exit => unreachable!(),
     };
@@ -69,81 +142,442 @@ pub fn pager(
         .margin(1)
         .split(f.area());
 
-    let commit_paragraph = Paragraph::new(commit.unwrap_or_default()).block(
+    let commit_paragraph = Paragraph::new(context).block(
         Block::default()
             .borders(Borders::BOTTOM)
-            .border_type(BorderType::Double),
+            .border_type(BorderType::Double)
+            .border_style(Style::new().fg(Color::DarkGray)),
     );
     f.render_widget(commit_paragraph, chunks[0]);
 
-    let paragraph = if let Some(hilights) = hilights {
-        let hilighted_log: Vec<_> = git_log
-            .iter()
-            .map(|line| {
-                let ac = AhoCorasick::builder()
-                    .ascii_case_insensitive(true)
-                    .build([hilights.as_str()])?;
-                let matches = ac.find_iter(line);
-                let hilights: Vec<_> = matches.map(|m| (m.start(), m.end())).collect();
-                debug!("Got hilights at: {hilights:?}");
-                let line_hilighted = hilights
-                    .windows(2)
-                    .map(<&[(usize, usize); 2]>::try_from)
-                    .collect::<Result<Vec<_>, _>>()?
-                    .iter()
-                    .fold(
-                        vec![Span::from(
-                            line[0..hilights.first().map_or(line.chars().count(), |m| m.0)]
-                                .to_string(),
-                        )],
-                        |mut coll, [(start, end), (next_start, _next_end)]| {
-                            let hilight = Span::styled(&line[*start..*end], hilight_style);
-                            let text_between_hilights = Span::from(&line[*end..*next_start]);
-                            coll.append(&mut vec![hilight, text_between_hilights]);
-                            coll
-                            // debug!("Adding: `{hilight}` and `{text_between_hilights}`");
-                        },
-                    );
-                let line_hilighted = if let Some((last_start, last_end)) = hilights.last() {
-                    let hilight = Span::styled(&line[*last_start..*last_end], hilight_style);
-                    let rest_of_line = Span::from(&line[*last_end..]);
-                    Line::from(
-                        vec![line_hilighted, vec![hilight], vec![rest_of_line]]
-                            .into_iter()
-                            .flatten()
-                            .collect::<Vec<_>>(),
-                    )
-                } else {
-                    Line::from(line_hilighted)
-                };
-                Ok::<Line, Error>(line_hilighted)
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-        Paragraph::new(hilighted_log)
-    } else {
-        Paragraph::new(git_log.join("\n"))
-    };
+    let paragraph = Paragraph::new(log_lines(screen, cf, chunks[1].width));
     f.render_widget(paragraph, chunks[1]);
+    let gutter_width = u16::try_from(gutter_width(screen))?;
     *vertical_size = chunks[1].height;
+    *horizontal_size = chunks[1].width.saturating_sub(gutter_width);
+
+    if let Some(area) = chunks.last() {
+        draw_status_bar(f, *area, screen);
+    }
 
     match state {
         State::Search(SearchState::GetInput { term }) => {
-            draw_search_box(f, chunks[2], term);
+            draw_search_box(f, chunks[2], "Search", term, screen);
         }
         State::Search(SearchState::Searching {
             term,
             position: _position,
         }) => {
-            draw_search_box(f, chunks[2], term);
+            draw_search_box(f, chunks[2], "Search", term, screen);
+        }
+        State::Filter { term } => {
+            draw_search_box(f, chunks[2], "Show only lines matching", term, screen);
         }
+        State::Command { term } => draw_command_box(f, chunks[2], term),
+        State::Picker(picker) => draw_picker(f, picker),
         State::Pager => (),
         State::Exit => unreachable!(),
     }
     Ok(())
 }
 
-fn draw_search_box(f: &mut Frame, area: Rect, input: &Input) {
-    let search_box =
-        Paragraph::new(input.value()).block(Block::default().borders(Borders::ALL).title("Search"));
+/// Lines of the context pane.
+///
+/// Levels are collapsed to their one line summary starting from the outermost
+/// one until the context fits in `max_lines`.
+fn context_lines<'a>(
+    contexts: &'a [Context],
+    cf: &ContextFinder,
+    max_lines: usize,
+) -> Vec<Line<'a>> {
+    let mut total: usize = contexts.iter().map(|context| context.lines.len()).sum();
+    contexts
+        .iter()
+        .flat_map(|context| {
+            if total > max_lines {
+                total -= context.lines.len().saturating_sub(1);
+                let kind = match context.boundary {
+                    Boundary::Commit => LineKind::Commit,
+                    Boundary::File => LineKind::FileHeader,
+                    Boundary::Hunk => LineKind::Hunk,
+                };
+                vec![Line::styled(context.summary.as_str(), line_style(kind))]
+            } else {
+                context
+                    .lines
+                    .iter()
+                    .map(|line| Line::styled(line.as_str(), line_style(cf.line_kind(line))))
+                    .collect()
+            }
+        })
+        .collect()
+}
+
+/// Lines of the rows of `screen` to show in an area `width` columns wide, in
+/// one or two columns, wrapped or scrolled sideways.
+fn log_lines<'a>(screen: &Screen<'a>, cf: &ContextFinder, width: u16) -> Vec<Line<'a>> {
+    // The gutter stays in place when the lines are scrolled sideways.
+    let lines_width = usize::from(width).saturating_sub(gutter_width(screen));
+
+    if let Some(split) = screen.split {
+        let column_width = usize::from(width.saturating_sub(1) / 2);
+        // Each column shows the line numbers of its own version.
+        let column = |index: Option<usize>, gutter: fn(&Gutter) -> &str| {
+            let spans = index.map_or_else(Vec::new, |index| {
+                let spans = row_spans(screen, index, cf).unwrap_or_default();
+                with_gutter(
+                    screen.gutters.get(index).map_or("", gutter),
+                    skip_columns(spans, screen.horizontal_scroll),
+                )
+            });
+            fit(spans, column_width)
+        };
+        split
+            .iter()
+            .filter_map(|split_row| match split_row {
+                SplitRow::Full(index) => row_spans(screen, *index, cf).map(|spans| {
+                    Line::from(with_gutter(
+                        unified_gutter(screen, *index),
+                        skip_columns(spans, screen.horizontal_scroll),
+                    ))
+                }),
+                SplitRow::Pair(old, new) => {
+                    let mut spans = column(*old, |gutter| &gutter.old);
+                    spans.push(Span::styled("\u{2502}", Style::new().fg(Color::DarkGray)));
+                    spans.extend(column(*new, |gutter| &gutter.new));
+                    Some(Line::from(spans))
+                }
+            })
+            .collect()
+    } else if screen.wrap {
+        (0..screen.rows.len())
+            .filter_map(|index| Some((index, row_spans(screen, index, cf)?)))
+            .flat_map(|(index, spans)| {
+                // A fold summary is cut at the edge of the screen, so that a
+                // folded row takes a single line.
+                let parts = if let Some(Some(_)) = screen.folds.get(index) {
+                    vec![fit(spans, lines_width)]
+                } else {
+                    wrap_columns(spans, lines_width)
+                };
+                parts
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(part, spans)| (index, part, spans))
+            })
+            .map(|(index, part, spans)| {
+                if part == 0 {
+                    Line::from(with_gutter(unified_gutter(screen, index), spans))
+                } else {
+                    let padding = " ".repeat(unified_gutter(screen, index).len());
+                    let mut line = vec![Span::raw(padding)];
+                    line.extend(spans);
+                    Line::from(line)
+                }
+            })
+            .collect()
+    } else {
+        (0..screen.rows.len())
+            .filter_map(|index| {
+                let spans = row_spans(screen, index, cf)?;
+                Some(Line::from(with_gutter(
+                    unified_gutter(screen, index),
+                    skip_columns(spans, screen.horizontal_scroll),
+                )))
+            })
+            .collect()
+    }
+}
+
+/// Number of columns taken by the widest gutter of the rows of `screen`.
+fn gutter_width(screen: &Screen) -> usize {
+    screen
+        .gutters
+        .iter()
+        .map(|gutter| gutter.unified.len())
+        .max()
+        .unwrap_or_default()
+}
+
+/// Gutter of row `index` shown across the screen.
+fn unified_gutter<'a>(screen: &Screen<'a>, index: usize) -> &'a str {
+    screen
+        .gutters
+        .get(index)
+        .map_or("", |gutter| gutter.unified.as_str())
+}
+
+/// Prepends `gutter` to `spans`.
+fn with_gutter<'a>(gutter: &'a str, spans: Vec<Span<'a>>) -> Vec<Span<'a>> {
+    let mut line = vec![Span::styled(gutter, Style::new().fg(Color::DarkGray))];
+    line.extend(spans);
+    line
+}
+
+/// Styled text of the row at `index` of `screen.rows`.
+fn row_spans<'a>(screen: &Screen<'a>, index: usize, cf: &ContextFinder) -> Option<Vec<Span<'a>>> {
+    let line_num = screen.rows.get(index)?;
+    let line = screen.lines.get(*line_num)?;
+    if let Some(Some(fold)) = screen.folds.get(index) {
+        return Some(fold_spans(line, fold, cf));
+    }
+    let kind = cf.line_kind(line);
+    let styles = screen.styles.get(*line_num)?;
+    // Colors of the input take precedence over highlighting the code.
+    let colors = screen
+        .syntax
+        .get(index)
+        .filter(|colors| !colors.is_empty() && styles.is_empty());
+    let mut spans = if let Some(colors) = colors {
+        let spans = colors.iter().fold(
+            vec![Span::styled(line.as_str(), tinted_style(kind))],
+            |spans, (start, end, color)| overlay(spans, &[(*start, *end)], Style::new().fg(*color)),
+        );
+        overlay(spans, &[(0, 1)], line_style(kind))
+    } else {
+        styles.iter().fold(
+            vec![Span::styled(line.as_str(), line_style(kind))],
+            |spans, (range, style)| overlay(spans, &[(range.start, range.end)], *style),
+        )
+    };
+    if let Some(changed) = screen.changed.get(index) {
+        spans = overlay(spans, changed, changed_style(kind, colors.is_some()));
+    }
+    if let Some(matcher) = screen.hilights {
+        let hilights = matcher.find_ranges(line);
+        debug!("Got hilights at: {hilights:?}");
+        let hilight_style = Style::new().fg(Color::Black).bg(Color::Gray);
+        Some(overlay(spans, &hilights, hilight_style))
+    } else {
+        Some(spans)
+    }
+}
+
+/// Summary of a folded section starting with `line`, such as
+/// `Cargo.lock: +1204 −980, folded`.
+fn fold_spans<'a>(line: &'a str, fold: &Fold, cf: &ContextFinder) -> Vec<Span<'a>> {
+    let (title, kind) = match fold.boundary {
+        Boundary::File => (cf.file_path(line).unwrap_or(line), LineKind::FileHeader),
+        Boundary::Commit | Boundary::Hunk => (line, LineKind::Hunk),
+    };
+    vec![
+        Span::styled(title, line_style(kind)),
+        Span::styled(": ", Style::new().fg(Color::DarkGray)),
+        Span::styled(format!("+{}", fold.added), line_style(LineKind::Added)),
+        Span::raw(" "),
+        Span::styled(
+            format!("\u{2212}{}", fold.removed),
+            line_style(LineKind::Removed),
+        ),
+        Span::styled(", folded", Style::new().fg(Color::DarkGray)),
+    ]
+}
+
+/// Background of a removed or added line whose code is highlighted.
+fn tinted_style(kind: LineKind) -> Style {
+    match kind {
+        LineKind::Added => Style::new().bg(Color::Rgb(0x00, 0x30, 0x10)),
+        LineKind::Removed => Style::new().bg(Color::Rgb(0x40, 0x08, 0x08)),
+        LineKind::Commit | LineKind::FileHeader | LineKind::Hunk | LineKind::Other => Style::new(),
+    }
+}
+
+/// Style of the changed words of a removed or added line, keeping the colors of
+/// the code if it is highlighted.
+fn changed_style(kind: LineKind, highlighted: bool) -> Style {
+    match (kind, highlighted) {
+        (LineKind::Added, true) => Style::new().bg(Color::Rgb(0x00, 0x60, 0x20)),
+        (LineKind::Removed, true) => Style::new().bg(Color::Rgb(0x80, 0x10, 0x10)),
+        (LineKind::Added, false) => Style::new().fg(Color::Black).bg(Color::Green),
+        (LineKind::Removed, false) => Style::new().fg(Color::Black).bg(Color::Red),
+        (LineKind::Commit | LineKind::FileHeader | LineKind::Hunk | LineKind::Other, _) => {
+            Style::new().add_modifier(Modifier::REVERSED)
+        }
+    }
+}
+
+/// Style of a line of the given kind.
+fn line_style(kind: LineKind) -> Style {
+    match kind {
+        LineKind::Commit => Style::new().fg(Color::Yellow),
+        LineKind::FileHeader => Style::new().add_modifier(Modifier::BOLD),
+        LineKind::Hunk => Style::new().fg(Color::Cyan),
+        LineKind::Added => Style::new().fg(Color::Green),
+        LineKind::Removed => Style::new().fg(Color::Red),
+        LineKind::Other => Style::new(),
+    }
+}
+
+fn draw_search_box(f: &mut Frame, area: Rect, label: &str, input: &Input, screen: &Screen) {
+    let options = screen.search_options;
+    let case = match options.case {
+        CaseMode::Insensitive => "ignore case",
+        CaseMode::Sensitive => "match case",
+        CaseMode::Smart => "smart case",
+    };
+    let mut title = if options.regex {
+        format!("{label} (regex, {case})")
+    } else {
+        format!("{label} ({case})")
+    };
+    if screen.search_error {
+        title.push_str(" - invalid pattern");
+    }
+    let mut block = Block::default().borders(Borders::ALL).title(title);
+    if let Some(matches) = screen.matches {
+        block = block.title_top(Line::from(match_status(matches)).right_aligned());
+    }
+    let search_box = Paragraph::new(input.value()).block(block);
     f.render_widget(search_box, area);
 }
+
+/// Draws the box for entering commands, such as `author alice`.
+fn draw_command_box(f: &mut Frame, area: Rect, input: &Input) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Command (author, message, since, until, all, fold or unfold)");
+    let command_box = Paragraph::new(format!(":{}", input.value())).block(block);
+    f.render_widget(command_box, area);
+}
+
+/// Draws the status bar with the message on the left and the position and
+/// state of the input on the right, such as
+/// `b8e882d  git  line 25/1204  12%  EOF`.
+fn draw_status_bar(f: &mut Frame, area: Rect, screen: &Screen) {
+    let status = &screen.status;
+    let mut info: Vec<String> = Vec::new();
+    if let Some(commit) = status.commit {
+        info.push(commit.to_string());
+    }
+    info.push(status.input_type.name().to_string());
+    if let Some(line) = status.line {
+        info.push(format!("line {}/{}", line + 1, status.total_lines));
+    }
+    info.push(format!("{}%", status.percent));
+    info.push(if status.streaming { "streaming" } else { "EOF" }.to_string());
+    let info = format!(" {} ", info.join("  "));
+    let [message_area, info_area] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(u16::try_from(info.chars().count()).unwrap_or(u16::MAX)),
+    ])
+    .areas(area);
+    let bar_style = Style::new().fg(Color::White).bg(Color::DarkGray);
+    let message = screen.message.unwrap_or_default();
+    f.render_widget(
+        Paragraph::new(message).style(bar_style.fg(Color::Yellow)),
+        message_area,
+    );
+    f.render_widget(Paragraph::new(info).style(bar_style), info_area);
+}
+
+/// Draws a picker popup over the pager.
+fn draw_picker(f: &mut Frame, picker: &Picker) {
+    let area = centered(f.area(), PICKER_SIZE_PERCENT);
+    f.render_widget(Clear, area);
+    let title = format!(
+        "{} ({}/{})",
+        picker.title,
+        picker.num_matches(),
+        picker.num_items()
+    );
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let [query_area, list_area] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+    f.render_widget(
+        Paragraph::new(format!("> {}", picker.query.value())),
+        query_area,
+    );
+    let items: Vec<ListItem> = picker
+        .matches()
+        .map(|item| ListItem::new(item.text.as_str()))
+        .collect();
+    let list = List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    let mut list_state = ListState::default().with_selected(picker.selected());
+    f.render_stateful_widget(list, list_area, &mut list_state);
+}
+
+/// Area of `percent` of the width and height of `area` in the middle of it.
+fn centered(area: Rect, percent: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Percentage(percent)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Percentage(percent)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Status of the search shown in the search box, such as "3/47 matches".
+fn match_status(matches: MatchCount) -> String {
+    let plural = if matches.total == 1 { "" } else { "es" };
+    match matches {
+        MatchCount { total: 0, .. } => "no matches".to_string(),
+        MatchCount {
+            current: Some(current),
+            total,
+        } => format!("{current}/{total} match{plural}"),
+        MatchCount {
+            current: None,
+            total,
+        } => format!("{total} match{plural}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{log_lines, Screen, Status};
+    use crate::context_finder::{Boundary, ContextFinder, InputType};
+    use crate::fold::Fold;
+    use crate::search::SearchOptions;
+
+    #[test]
+    fn wrap_rows_but_not_fold_summaries() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let lines = [
+            "diff --git a/src/a_very_long_path/main.rs b/src/a_very_long_path/main.rs",
+            "+a line wider than the screen",
+        ]
+        .map(ToString::to_string);
+        let fold = Fold {
+            boundary: Boundary::File,
+            added: 1,
+            removed: 0,
+        };
+        let screen = Screen {
+            lines: &lines,
+            styles: &[Vec::new(), Vec::new()],
+            rows: &[0, 1],
+            folds: &[Some(fold), None],
+            changed: &[Vec::new(), Vec::new()],
+            syntax: &[Vec::new(), Vec::new()],
+            gutters: &[],
+            split: None,
+            contexts: &[],
+            hilights: None,
+            search_options: SearchOptions::default(),
+            search_error: false,
+            matches: None,
+            message: None,
+            horizontal_scroll: 0,
+            wrap: true,
+            status: Status {
+                line: Some(0),
+                total_lines: lines.len(),
+                percent: 100,
+                commit: None,
+                input_type: InputType::Git,
+                streaming: false,
+            },
+        };
+        let rendered: Vec<String> = log_lines(&screen, &cf, 20)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            rendered,
+            ["src/a_very_long_path", "+a line wider than t", "he screen"]
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use crate::error::Error;
+use crate::highlight::wrap_columns;
+use ratatui::text::Span;
 use tracing::trace;
+use unicode_width::UnicodeWidthStr;
 
 /// Decrements the scroll position by a specified count ensuring the result does
 /// not underflow.
@@ -8,7 +11,7 @@ use tracing::trace;
 /// * `usize` - The new scroll position, or 0 if the result would be negative.
 #[must_use]
 pub fn decrement_scroll_position(scroll: usize, count: usize) -> usize {
-    scroll.checked_sub(count).unwrap_or_default()
+    scroll.saturating_sub(count)
 }
 
 /// Increments the scroll position by a specified count, ensuring it does not
@@ -19,6 +22,8 @@ pub fn decrement_scroll_position(scroll: usize, count: usize) -> usize {
 /// * `count` - The amount to increment the scroll position by.
 /// * `max_val` - The maximum allowable value for the scroll position.
 /// * `vertical_size` - The vertical size of the display.
+/// * `row_height` - The number of lines of the display a row takes, more than
+///   one for wrapped rows.
 ///
 /// # Returns
 ///
@@ -29,10 +34,13 @@ pub fn increment_scroll_position(
     count: usize,
     max_val: usize,
     vertical_size: u16,
+    row_height: impl Fn(usize) -> usize,
 ) -> usize {
     if let Some(pos) = scroll.checked_add(count) {
-        if pos > (max_val - usize::from(vertical_size)) {
-            max_val - usize::from(vertical_size)
+        let last_screen = rows_fitting((0..max_val).rev(), vertical_size, row_height);
+        let max_pos = max_val.saturating_sub(last_screen);
+        if pos > max_pos {
+            max_pos
         } else {
             pos
         }
@@ -43,23 +51,135 @@ pub fn increment_scroll_position(
 
 /// Retrieves a screenful of lines from the log starting at a specified position.
 ///
-/// This function returns a slice of strings representing a screenful of lines
-/// from the log, starting at the given position and extending for the vertical
-/// size of the display.
+/// This function returns a slice representing a screenful of lines from the
+/// log, or of data kept per line of the log, starting at the given position
+/// and extending for the vertical size of the display. A row not fitting
+/// completely at the bottom of the display is included.
+///
+/// # Arguments
+/// * `log_lines` - The rows of the log.
+/// * `position` - The row at the top of the display.
+/// * `vertical_size` - The vertical size of the display.
+/// * `row_height` - The number of lines of the display a row takes, more than
+///   one for wrapped rows.
 ///
 /// # Errors
 /// This function can return errors in the following cases:
 /// * If the specified range is out of bounds of the log lines.
-pub fn get_lines(
-    log_lines: &[String],
+pub fn get_lines<T>(
+    log_lines: &[T],
     position: usize,
     vertical_size: u16,
-) -> Result<&[String], Error> {
+    row_height: impl Fn(usize) -> usize,
+) -> Result<&[T], Error> {
     trace!("Getting screenful of lines");
-    let lines = if log_lines.len() > (position + usize::from(vertical_size)) {
-        log_lines.get(position..(position + usize::from(vertical_size)))
-    } else {
-        log_lines.get(position..(log_lines.len() - 1))
-    };
-    lines.ok_or(Error::GetLines)
+    let mut used = 0;
+    let count = (position..log_lines.len())
+        .take_while(|row| {
+            let top = used;
+            used += row_height(*row);
+            top < usize::from(vertical_size)
+        })
+        .count();
+    log_lines
+        .get(position..position + count)
+        .ok_or(Error::GetLines)
+}
+
+/// Share of the `num_rows` rows down to the bottom of a screen of `height`
+/// rows starting at row `position`, in percent.
+#[must_use]
+pub fn percent(position: usize, height: usize, num_rows: usize) -> usize {
+    if num_rows == 0 {
+        return 100;
+    }
+    (position + height).min(num_rows) * 100 / num_rows
+}
+
+/// Number of lines of the screen `line` takes when wrapped at `width` columns
+/// by `wrap_columns`.
+#[must_use]
+pub fn wrapped_height(line: &str, width: u16) -> usize {
+    if line.width() <= usize::from(width) {
+        return 1;
+    }
+    wrap_columns(vec![Span::raw(line)], usize::from(width)).len()
+}
+
+/// Counts the rows fitting on a screen of `vertical_size` lines.
+///
+/// # Arguments
+/// * `rows` - The rows in the order they are put on the screen, such as from
+///   the last row backwards for the last screen.
+/// * `vertical_size` - The vertical size of the display.
+/// * `row_height` - The number of lines of the screen a row takes.
+///
+/// # Returns
+/// * `usize` - The number of rows fitting completely, or one if already the
+///   first row does not fit.
+#[must_use]
+pub fn rows_fitting(
+    rows: impl Iterator<Item = usize>,
+    vertical_size: u16,
+    row_height: impl Fn(usize) -> usize,
+) -> usize {
+    let mut lines = 0;
+    let mut count = 0;
+    for row in rows {
+        lines += row_height(row);
+        if lines > usize::from(vertical_size) {
+            break;
+        }
+        count += 1;
+    }
+    count.max(1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{get_lines, increment_scroll_position, percent, rows_fitting, wrapped_height};
+
+    #[test]
+    fn scroll_wrapped_rows() {
+        let rows: Vec<usize> = (0..6).collect();
+        let heights = [1, 3, 2, 1, 1, 2];
+        let height = |row: usize| heights[row];
+        assert_eq!(get_lines(&rows, 0, 4, height).unwrap(), [0, 1]);
+        assert_eq!(get_lines(&rows, 1, 4, height).unwrap(), [1, 2]);
+        assert_eq!(get_lines(&rows, 2, 4, height).unwrap(), [2, 3, 4]);
+        assert_eq!(get_lines(&rows, 0, 4, |_row| 1).unwrap(), [0, 1, 2, 3]);
+        assert!(get_lines(&rows, 7, 4, height).is_err());
+        assert_eq!(increment_scroll_position(0, 2, 6, 4, height), 2);
+        assert_eq!(increment_scroll_position(0, 5, 6, 4, height), 3);
+        assert_eq!(increment_scroll_position(0, 5, 6, 4, |_row| 1), 2);
+        assert_eq!(increment_scroll_position(0, 5, 2, 4, height), 0);
+    }
+
+    #[test]
+    fn percent_shown() {
+        assert_eq!(percent(0, 10, 0), 100);
+        assert_eq!(percent(0, 10, 40), 25);
+        assert_eq!(percent(10, 10, 40), 50);
+        assert_eq!(percent(35, 10, 40), 100);
+        assert_eq!(percent(0, 10, 3), 100);
+        assert_eq!(percent(1, 1, 3), 66);
+    }
+
+    #[test]
+    fn wrapped_rows() {
+        assert_eq!(wrapped_height("", 10), 1);
+        assert_eq!(wrapped_height("0123456789", 10), 1);
+        assert_eq!(wrapped_height("0123456789a", 10), 2);
+        assert_eq!(wrapped_height("日本語", 4), 2);
+        // A wide character crossing the edge moves to the next line.
+        assert_eq!(wrapped_height("日本語", 3), 3);
+        assert_eq!(wrapped_height("a日本", 4), 2);
+        assert_eq!(wrapped_height("日本", 1), 2);
+        let heights = [1, 3, 2, 1];
+        let height = |row: usize| heights[row];
+        assert_eq!(rows_fitting(0..4, 4, height), 2);
+        assert_eq!(rows_fitting(1..4, 5, height), 2);
+        assert_eq!(rows_fitting((0..4).rev(), 3, height), 2);
+        assert_eq!(rows_fitting(1..2, 2, height), 1);
+    }
 }
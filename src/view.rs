@@ -0,0 +1,332 @@
+use crate::commit_filter::CommitFilter;
+use crate::context_finder::{Boundary, ContextFinder};
+use crate::fold::{Fold, Folds};
+use crate::search::Matcher;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Maps the rows of the pager to the lines of the input they show.
+///
+/// Without filters every line is shown. A line filter shows only the lines
+/// matching it, like with `&pattern` in `less`, and a commit filter hides
+/// whole commits not meeting its criteria. Folded file diffs and hunks are
+/// shown as their first line only.
+pub struct View {
+    filter: Option<Matcher>,
+    commit_filter: CommitFilter,
+    folds: Folds,
+    /// Line numbers of the shown lines in ascending order.
+    rows: Vec<usize>,
+    /// Number of lines checked against the filters so far.
+    checked: usize,
+    /// Whether the commit of the last checked line is shown.
+    in_shown_commit: bool,
+    /// Whether the last checked line is inside a hunk.
+    in_hunk: bool,
+    /// First line of the folded section the last checked line belongs to.
+    folded: Option<usize>,
+    /// The folded sections by their first line.
+    fold_stats: HashMap<usize, Fold>,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            filter: None,
+            commit_filter: CommitFilter::default(),
+            folds: Folds::default(),
+            rows: Vec::new(),
+            checked: 0,
+            in_shown_commit: true,
+            in_hunk: false,
+            folded: None,
+            fold_stats: HashMap::new(),
+        }
+    }
+}
+
+impl View {
+    /// Sets the filter hiding the lines not matching it, or shows all lines
+    /// with None. The rows are rebuilt on the next `update`.
+    pub fn set_filter(&mut self, filter: Option<Matcher>) {
+        self.filter = filter;
+        self.reset();
+    }
+
+    /// The criteria of the commits shown.
+    #[must_use]
+    pub fn commit_filter(&self) -> &CommitFilter {
+        &self.commit_filter
+    }
+
+    /// Sets the criteria of the commits shown. The rows are rebuilt on the
+    /// next `update`.
+    pub fn set_commit_filter(&mut self, commit_filter: CommitFilter) {
+        self.commit_filter = commit_filter;
+        self.reset();
+    }
+
+    /// The file diffs and hunks to show folded.
+    #[must_use]
+    pub fn folds(&self) -> &Folds {
+        &self.folds
+    }
+
+    /// Sets the file diffs and hunks to show folded. The rows are rebuilt on
+    /// the next `update`.
+    pub fn set_folds(&mut self, folds: Folds) {
+        self.folds = folds;
+        self.reset();
+    }
+
+    /// The summary of the folded section starting at line `line_num`, if it is
+    /// folded.
+    #[must_use]
+    pub fn fold(&self, line_num: usize) -> Option<&Fold> {
+        self.fold_stats.get(&line_num)
+    }
+
+    fn reset(&mut self) {
+        self.rows.clear();
+        self.checked = 0;
+        self.in_shown_commit = true;
+        self.in_hunk = false;
+        self.folded = None;
+        self.fold_stats.clear();
+    }
+
+    /// Whether lines are hidden by a filter.
+    #[must_use]
+    pub fn is_filtered(&self) -> bool {
+        self.filter.is_some() || !self.commit_filter.is_empty()
+    }
+
+    /// Adds the rows of the lines not checked yet against the filters.
+    ///
+    /// Checking stops at a commit whose header has not been read completely
+    /// until more lines are read, or until `at_end` tells that the whole input
+    /// has been read.
+    pub fn update(&mut self, all_lines: &[String], cf: &ContextFinder, at_end: bool) {
+        for (line_num, line) in all_lines.iter().enumerate().skip(self.checked) {
+            let mut in_fold = false;
+            if cf.is_start(Boundary::Commit, line) {
+                if !self.commit_filter.is_empty() {
+                    let Some(commit) = cf.get_commit_header(all_lines, line_num, at_end) else {
+                        return;
+                    };
+                    self.in_shown_commit = self.commit_filter.matches(&commit);
+                }
+                self.in_hunk = false;
+                self.folded = None;
+            } else if let Some(path) = cf.file_path(line) {
+                self.in_hunk = false;
+                self.folded = self.start_fold(line_num, Boundary::File, Some(path));
+            } else if cf.is_start(Boundary::Hunk, line) {
+                self.in_hunk = true;
+                if self.in_folded(Boundary::File) {
+                    in_fold = true;
+                } else {
+                    self.folded = self.start_fold(line_num, Boundary::Hunk, None);
+                }
+            } else if let Some(start) = self.folded {
+                in_fold = true;
+                if let (true, Some(fold)) = (self.in_hunk, self.fold_stats.get_mut(&start)) {
+                    if line.starts_with('+') {
+                        fold.added += 1;
+                    } else if line.starts_with('-') {
+                        fold.removed += 1;
+                    }
+                }
+            }
+            // The summary of a folded section is shown even if it does not
+            // match the filter.
+            let starts_fold = self.folded == Some(line_num);
+            if self.in_shown_commit
+                && !in_fold
+                && (starts_fold
+                    || self
+                        .filter
+                        .as_ref()
+                        .is_none_or(|filter| filter.is_match(line)))
+            {
+                self.rows.push(line_num);
+            }
+            self.checked = line_num + 1;
+        }
+    }
+
+    /// Starts a folded section at line `line_num` if the section starting there
+    /// is folded.
+    fn start_fold(
+        &mut self,
+        line_num: usize,
+        boundary: Boundary,
+        path: Option<&str>,
+    ) -> Option<usize> {
+        if !self.folds.is_folded(line_num, path) {
+            return None;
+        }
+        self.fold_stats.insert(
+            line_num,
+            Fold {
+                boundary,
+                added: 0,
+                removed: 0,
+            },
+        );
+        Some(line_num)
+    }
+
+    /// Whether the last checked line is inside a folded section of kind
+    /// `boundary`.
+    fn in_folded(&self, boundary: Boundary) -> bool {
+        self.folded
+            .and_then(|start| self.fold_stats.get(&start))
+            .is_some_and(|fold| fold.boundary == boundary)
+    }
+
+    /// Line numbers of the shown lines in ascending order, indexed by row.
+    #[must_use]
+    pub fn rows(&self) -> &[usize] {
+        &self.rows
+    }
+
+    /// Number of rows shown.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Line number of the line shown at `row`.
+    #[must_use]
+    pub fn line(&self, row: usize) -> Option<usize> {
+        self.rows.get(row).copied()
+    }
+
+    /// Row of the first shown line at or after `line_num`.
+    ///
+    /// # Returns
+    /// * `usize` - The row, or the number of rows if no line is shown at or
+    ///   after `line_num`.
+    #[must_use]
+    pub fn row_of(&self, line_num: usize) -> usize {
+        self.rows.partition_point(|row_line| *row_line < line_num)
+    }
+
+    /// Whether any of the lines in `lines` is shown.
+    #[must_use]
+    pub fn shows_any(&self, lines: Range<usize>) -> bool {
+        self.line(self.row_of(lines.start))
+            .is_some_and(|line_num| line_num < lines.end)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::View;
+    use crate::commit_filter::CommitFilter;
+    use crate::context_finder::{Boundary, ContextFinder, InputType};
+    use crate::fold::{Fold, Folds};
+    use crate::search::{Matcher, SearchOptions};
+
+    fn lines() -> Vec<String> {
+        [
+            "commit b8e882d50a8e2f184e8803a18818da18dbbd1469",
+            "Author: Alice <alice@example.com>",
+            "",
+            "    Read stdin",
+            "",
+            "diff --git a/src/main.rs b/src/main.rs",
+            "+added",
+            " context",
+            "commit 0123456789012345678901234567890123456789",
+            "Author: Bob <bob@example.com>",
+            "",
+            "    Add tests",
+            "",
+            "diff --git a/src/lib.rs b/src/lib.rs",
+            "+more",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect()
+    }
+
+    #[test]
+    fn unfiltered() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let mut view = View::default();
+        view.update(&lines()[..2], &cf, false);
+        assert_eq!(view.rows(), [0, 1]);
+        view.update(&lines(), &cf, false);
+        assert_eq!(view.len(), 15);
+        assert_eq!(view.row_of(3), 3);
+        assert!(!view.is_filtered());
+    }
+
+    #[test]
+    fn filtered() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let mut view = View::default();
+        view.set_filter(Some(Matcher::new("+", SearchOptions::default()).unwrap()));
+        view.update(&lines(), &cf, false);
+        assert_eq!(view.rows(), [6, 14]);
+        assert_eq!(view.line(1), Some(14));
+        assert_eq!(view.row_of(7), 1);
+        assert_eq!(view.row_of(15), 2);
+        view.set_filter(None);
+        view.update(&lines(), &cf, false);
+        assert_eq!(view.len(), 15);
+    }
+
+    #[test]
+    fn commit_filtered() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let mut view = View::default();
+        let mut commit_filter = CommitFilter::default();
+        commit_filter.apply("author bob").unwrap();
+        view.set_commit_filter(commit_filter);
+        view.update(&lines()[..10], &cf, false);
+        assert!(view.is_empty());
+        view.update(&lines(), &cf, false);
+        assert_eq!(view.rows(), (8..15).collect::<Vec<_>>());
+        view.set_filter(Some(Matcher::new("+", SearchOptions::default()).unwrap()));
+        view.update(&lines(), &cf, true);
+        assert_eq!(view.rows(), [14]);
+        assert!(!view.shows_any(0..8));
+        assert!(view.shows_any(8..15));
+    }
+
+    #[test]
+    fn folded() {
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let mut lines = lines();
+        lines.insert(6, "@@ -1,2 +1,2 @@".to_string());
+        lines.insert(15, "@@ -1 +1 @@".to_string());
+        let mut view = View::default();
+        let mut folds = Folds::default();
+        folds.add_glob("src/main.rs").unwrap();
+        folds.toggle(15);
+        view.set_folds(folds);
+        view.update(&lines, &cf, true);
+        assert_eq!(view.rows(), [0, 1, 2, 3, 4, 5, 9, 10, 11, 12, 13, 14, 15]);
+        assert_eq!(
+            view.fold(5),
+            Some(&Fold {
+                boundary: Boundary::File,
+                added: 1,
+                removed: 0
+            })
+        );
+        assert_eq!(view.fold(15).unwrap().added, 1);
+        assert!(view.fold(14).is_none());
+        view.set_filter(Some(Matcher::new("Add", SearchOptions::default()).unwrap()));
+        view.update(&lines, &cf, true);
+        assert_eq!(view.rows(), [5, 12, 15]);
+    }
+}
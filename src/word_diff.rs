@@ -0,0 +1,247 @@
+use crate::context_finder::{ContextFinder, LineKind};
+use crate::side_by_side::{split_rows, SplitRow};
+
+/// Maximum product of the numbers of words in a pair of lines diffed, bounding
+/// the time spent on very long lines.
+const MAX_DIFF_SIZE: usize = 40_000;
+
+/// Byte ranges of the changed words of a line.
+pub type Ranges = Vec<(usize, usize)>;
+
+/// Finds the changed words of the removed and added lines shown on the
+/// screen, pairing each run of removed lines line by line with the run of added
+/// lines following it.
+///
+/// # Arguments
+/// * `lines` - All lines of the input.
+/// * `rows` - Line numbers of all rows of the view.
+/// * `first` - Row shown at the top of the screen.
+/// * `count` - Number of rows shown.
+/// * `cf` - The `ContextFinder` used to classify the lines.
+/// * `in_hunk` - Whether the row at the top of the screen is inside a hunk.
+///
+/// # Returns
+/// * `Vec<Ranges>` - The byte ranges of the changed words of each row shown,
+///   empty for rows not paired.
+#[must_use]
+pub fn changed_words(
+    lines: &[String],
+    rows: &[usize],
+    first: usize,
+    count: usize,
+    cf: &ContextFinder,
+    in_hunk: bool,
+) -> Vec<Ranges> {
+    let mut changed = vec![Vec::new(); count];
+    let is_changed = |line_num: &usize| {
+        lines
+            .get(*line_num)
+            .is_some_and(|line| matches!(cf.line_kind(line), LineKind::Added | LineKind::Removed))
+    };
+    // Pair the lines of runs starting above or ending below the screen from
+    // their first to their last line.
+    let start = rows[..first.min(rows.len())]
+        .iter()
+        .rposition(|line_num| !is_changed(line_num))
+        .map_or(0, |row| row + 1);
+    let bottom = (first + count).min(rows.len());
+    let end = rows[bottom..]
+        .iter()
+        .position(|line_num| !is_changed(line_num))
+        .map_or(rows.len(), |row| bottom + row);
+    let Some(shown) = rows.get(start..end) else {
+        return changed;
+    };
+    for split_row in split_rows(lines, shown, cf, in_hunk) {
+        let SplitRow::Pair(Some(old), Some(new)) = split_row else {
+            continue;
+        };
+        // Lines hidden between the paired lines, such as by a filter, make
+        // them unrelated.
+        if old == new || shown[new] - shown[old] != new - old {
+            continue;
+        }
+        let (Some(old_line), Some(new_line)) = (lines.get(shown[old]), lines.get(shown[new]))
+        else {
+            continue;
+        };
+        // Skip the `-` and `+` markers.
+        let (Some(old_text), Some(new_text)) = (old_line.get(1..), new_line.get(1..)) else {
+            continue;
+        };
+        let (old_ranges, new_ranges) = diff_words(old_text, new_text);
+        for (row, ranges) in [(old, old_ranges), (new, new_ranges)] {
+            if let Some(row_changed) = (start + row)
+                .checked_sub(first)
+                .and_then(|index| changed.get_mut(index))
+            {
+                *row_changed = ranges
+                    .into_iter()
+                    .map(|(range_start, range_end)| (range_start + 1, range_end + 1))
+                    .collect();
+            }
+        }
+    }
+    changed
+}
+
+/// Diffs `old` and `new` word by word.
+///
+/// # Returns
+/// * `(Ranges, Ranges)` - The byte ranges of the words of `old` and `new` not
+///   common to both, or no ranges if the lines have no words in common or are
+///   too long.
+#[must_use]
+pub fn diff_words(old: &str, new: &str) -> (Ranges, Ranges) {
+    let old_words = words(old);
+    let new_words = words(new);
+    if old_words.len() * new_words.len() > MAX_DIFF_SIZE {
+        return (Vec::new(), Vec::new());
+    }
+    let old_words: Vec<&str> = old_words
+        .iter()
+        .map(|(start, end)| &old[*start..*end])
+        .collect();
+    let new_words: Vec<&str> = new_words
+        .iter()
+        .map(|(start, end)| &new[*start..*end])
+        .collect();
+    let (old_common, new_common) = common_words(&old_words, &new_words);
+    let has_common_word = old_words
+        .iter()
+        .zip(&old_common)
+        .any(|(word, common)| *common && !word.trim().is_empty());
+    if !has_common_word {
+        return (Vec::new(), Vec::new());
+    }
+    (
+        changed_ranges(&old_words, &old_common),
+        changed_ranges(&new_words, &new_common),
+    )
+}
+
+/// Splits `text` into words, runs of whitespace and single other characters.
+///
+/// # Returns
+/// * `Vec<(usize, usize)>` - The byte ranges of the parts covering all of
+///   `text`.
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Some(0)
+        } else if c.is_whitespace() {
+            Some(1)
+        } else {
+            None
+        }
+    };
+    let mut words: Vec<(usize, usize)> = Vec::new();
+    let mut previous = None;
+    for (offset, c) in text.char_indices() {
+        let current = class(c);
+        match words.last_mut() {
+            Some((_start, end)) if current.is_some() && current == previous => {
+                *end = offset + c.len_utf8();
+            }
+            _ => words.push((offset, offset + c.len_utf8())),
+        }
+        previous = current;
+    }
+    words
+}
+
+/// Marks the words of the longest common subsequence of `old` and `new`.
+fn common_words(old: &[&str], new: &[&str]) -> (Vec<bool>, Vec<bool>) {
+    // lengths[i][j] is the length of the longest common subsequence of
+    // old[i..] and new[j..].
+    let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut old_common = vec![false; old.len()];
+    let mut new_common = vec![false; new.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            old_common[i] = true;
+            new_common[j] = true;
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_common, new_common)
+}
+
+/// Byte ranges of the consecutive words not marked common, merged together.
+fn changed_ranges(words: &[&str], common: &[bool]) -> Ranges {
+    let mut ranges: Ranges = Vec::new();
+    let mut offset = 0;
+    let mut previous_changed = false;
+    for (word, common) in words.iter().zip(common) {
+        let end = offset + word.len();
+        if !common {
+            match ranges.last_mut() {
+                Some((_start, range_end)) if previous_changed => *range_end = end,
+                _ => ranges.push((offset, end)),
+            }
+        }
+        previous_changed = !common;
+        offset = end;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod test {
+    use super::{changed_words, diff_words};
+    use crate::context_finder::{ContextFinder, InputType};
+
+    #[test]
+    fn diff_by_word() {
+        let old = "let size = compute(a, b);";
+        let new = "let length = compute(a, b, c);";
+        let (old_ranges, new_ranges) = diff_words(old, new);
+        assert_eq!(old_ranges, [(4, 8)]);
+        assert_eq!(new_ranges, [(4, 10), (25, 28)]);
+        assert_eq!(&new[25..28], ", c");
+        assert_eq!(diff_words("foo()", "bar.baz"), (vec![], vec![]));
+        assert_eq!(diff_words("same", "same"), (vec![], vec![]));
+    }
+
+    #[test]
+    fn changed_words_of_shown_rows() {
+        let lines: Vec<String> = [
+            "@@ -1,3 +1,3 @@",
+            "-let a = 1;",
+            "-let b = 2;",
+            "+let a = 3;",
+            "+let b = 2;",
+        ]
+        .into_iter()
+        .map(ToString::to_string)
+        .collect();
+        let cf = ContextFinder::new(&InputType::Git).unwrap();
+        let rows: Vec<usize> = (0..lines.len()).collect();
+        assert_eq!(
+            changed_words(&lines, &rows, 0, 5, &cf, false),
+            [vec![], vec![(9, 10)], vec![], vec![(9, 10)], vec![]]
+        );
+        let changed = changed_words(&lines, &rows, 2, 3, &cf, true);
+        assert_eq!(changed, [vec![], vec![(9, 10)], vec![]]);
+        let changed = changed_words(&lines, &rows, 0, 2, &cf, false);
+        assert_eq!(changed, [vec![], vec![(9, 10)]]);
+        let filtered = [0, 1, 4];
+        let changed = changed_words(&lines, &filtered, 0, 3, &cf, false);
+        assert_eq!(changed, [vec![], vec![], vec![]]);
+    }
+}